@@ -1,16 +1,36 @@
 use crossterm::event;
 use ratatui::{DefaultTerminal, Frame};
-use std::{cell::RefCell, collections::HashMap, fmt::Debug, io, rc::Rc};
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fmt::Debug,
+    io,
+    rc::Rc,
+    time::{Duration, Instant},
+};
 
 use crate::{
+    backoff::Backoff,
+    clipboard::Clipboard,
     message_bus::{Message, MessageBus},
-    screens::{dashboard::Dashboard, welcome_screen::WelcomeScreen},
+    screens::{
+        create_account::CreateAccountScreen, dashboard::Dashboard, lock_screen::LockScreen,
+        welcome_screen::WelcomeScreen,
+    },
+    settings::Settings,
+    vault::store::VaultStore,
 };
 
+/// How often the active screen gets a tick, and is redrawn without any input.
+const TICK_RATE: Duration = Duration::from_millis(250);
+
 #[derive(PartialEq, Eq, Debug, Hash)]
 pub enum AppState {
     WelcomeScreen,
+    CreateAccount,
     Dashboard,
+    /// The dashboard was locked and waits for the master password.
+    Locked,
     AddNewPassword,
     Quit,
 }
@@ -18,6 +38,8 @@ pub trait Screen {
     fn handle_terminal_events(&mut self, event: event::Event, state: &mut AppState);
     fn render(&mut self, frame: &mut Frame);
     fn handle_messages(&mut self, messages: Vec<Message>, state: &mut AppState);
+    /// Called every [`TICK_RATE`] while the screen is active, for work that depends on time.
+    fn handle_tick(&mut self, state: &mut AppState);
 }
 
 pub struct App {
@@ -27,8 +49,14 @@ pub struct App {
 }
 
 impl App {
-    pub fn new() -> Self {
+    pub fn new(
+        vault_store: Rc<RefCell<dyn VaultStore>>,
+        clipboard: Clipboard,
+        settings: Settings,
+    ) -> Self {
         let message_bus = Rc::new(RefCell::new(MessageBus::new()));
+        // Failed attempts count against the account whichever screen they were made on.
+        let backoff = Rc::new(RefCell::new(Backoff::default()));
 
         let mut app = App {
             state: AppState::WelcomeScreen,
@@ -38,19 +66,50 @@ impl App {
 
         app.screens_map.insert(
             AppState::WelcomeScreen,
-            Box::new(WelcomeScreen::new(Rc::clone(&message_bus))),
+            Box::new(WelcomeScreen::new(
+                Rc::clone(&message_bus),
+                Rc::clone(&vault_store),
+                Rc::clone(&backoff),
+            )),
+        );
+
+        app.screens_map.insert(
+            AppState::CreateAccount,
+            Box::new(CreateAccountScreen::new(
+                Rc::clone(&message_bus),
+                Rc::clone(&vault_store),
+            )),
+        );
+
+        app.screens_map.insert(
+            AppState::Locked,
+            Box::new(LockScreen::new(
+                Rc::clone(&message_bus),
+                Rc::clone(&vault_store),
+                backoff,
+            )),
         );
 
         app.screens_map.insert(
             AppState::Dashboard,
-            Box::new(Dashboard::new(Rc::clone(&message_bus))),
+            Box::new(Dashboard::new(
+                Rc::clone(&message_bus),
+                Rc::clone(&vault_store),
+                clipboard,
+                settings,
+            )),
         );
 
         app
     }
 
     /// runs the application's main loop until the user quits
+    ///
+    /// Waits for terminal events only until the next tick or scheduled message is due, so the
+    /// screen keeps changing without any input.
     pub fn run(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        let mut last_tick = Instant::now();
+
         while self.state != AppState::Quit {
             let messages = self.message_bus.borrow_mut().poll_messages();
             let screen = self
@@ -62,8 +121,21 @@ impl App {
             screen.handle_messages(messages, &mut self.state);
             terminal.draw(|frame| screen.render(frame))?;
 
-            let ev = event::read()?;
-            screen.handle_terminal_events(ev, &mut self.state);
+            let now = Instant::now();
+            let until_tick = TICK_RATE.saturating_sub(now.duration_since(last_tick));
+            let timeout = match self.message_bus.borrow().next_deadline() {
+                Some(deadline) => until_tick.min(deadline.saturating_duration_since(now)),
+                None => until_tick,
+            };
+
+            if event::poll(timeout)? {
+                let ev = event::read()?;
+                screen.handle_terminal_events(ev, &mut self.state);
+            }
+            if last_tick.elapsed() >= TICK_RATE {
+                screen.handle_tick(&mut self.state);
+                last_tick = Instant::now();
+            }
         }
         Ok(())
     }
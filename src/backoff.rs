@@ -0,0 +1,58 @@
+use std::time::{Duration, Instant};
+
+/// Delay after the first failed login, doubled with every further failure.
+pub const BASE_BACKOFF: Duration = Duration::from_secs(1);
+pub const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Failed attempts at a master password in a row, and until when the next one has to wait.
+#[derive(Default)]
+pub struct Backoff {
+    failed_attempts: u32,
+    locked_until: Option<Instant>,
+}
+
+impl Backoff {
+    /// How long the next attempt still has to wait, if at all.
+    pub fn remaining(&self) -> Option<Duration> {
+        self.locked_until
+            .map(|until| until.saturating_duration_since(Instant::now()))
+            .filter(|remaining| !remaining.is_zero())
+    }
+
+    /// Counts a failed attempt, returning how many failed in a row and how long the next one
+    /// has to wait.
+    pub fn fail(&mut self) -> (u32, Duration) {
+        self.failed_attempts += 1;
+        let backoff = BASE_BACKOFF
+            .saturating_mul(2u32.saturating_pow(self.failed_attempts - 1))
+            .min(MAX_BACKOFF);
+        self.locked_until = Some(Instant::now() + backoff);
+
+        (self.failed_attempts, backoff)
+    }
+
+    pub fn reset(&mut self) {
+        *self = Backoff::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_up_to_its_maximum_until_reset() {
+        let mut backoff = Backoff::default();
+        assert_eq!(backoff.remaining(), None);
+
+        let waits: Vec<(u32, Duration)> = (0..7).map(|_| backoff.fail()).collect();
+        let seconds: Vec<u64> = waits.iter().map(|(_, wait)| wait.as_secs()).collect();
+        assert_eq!(seconds, [1, 2, 4, 8, 16, 30, 30]);
+        assert_eq!(waits.last().unwrap().0, 7);
+        assert!(backoff.remaining().is_some());
+
+        backoff.reset();
+        assert_eq!(backoff.remaining(), None);
+        assert_eq!(backoff.fail(), (1, BASE_BACKOFF));
+    }
+}
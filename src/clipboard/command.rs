@@ -0,0 +1,114 @@
+use std::{
+    env,
+    io::{self, Write},
+    process::{Command, Stdio},
+};
+
+use super::ClipboardBackend;
+
+/// Drives an external clipboard tool such as `wl-copy` or `xclip`.
+pub struct CommandClipboard {
+    name: &'static str,
+    copy: &'static [&'static str],
+    paste: &'static [&'static str],
+    /// `None` when the tool cannot clear, in which case an empty string is copied instead.
+    clear: Option<&'static [&'static str]>,
+}
+
+const WL_CLIPBOARD: CommandClipboard = CommandClipboard {
+    name: "wl-copy",
+    copy: &["wl-copy"],
+    paste: &["wl-paste", "--no-newline"],
+    clear: Some(&["wl-copy", "--clear"]),
+};
+
+const XCLIP: CommandClipboard = CommandClipboard {
+    name: "xclip",
+    copy: &["xclip", "-selection", "clipboard", "-in"],
+    paste: &["xclip", "-selection", "clipboard", "-out"],
+    clear: None,
+};
+
+const XSEL: CommandClipboard = CommandClipboard {
+    name: "xsel",
+    copy: &["xsel", "--clipboard", "--input"],
+    paste: &["xsel", "--clipboard", "--output"],
+    clear: Some(&["xsel", "--clipboard", "--clear"]),
+};
+
+impl CommandClipboard {
+    /// Chooses a tool matching the running display server, if one is installed.
+    pub fn detect() -> Option<Self> {
+        let candidates = if env::var_os("WAYLAND_DISPLAY").is_some() {
+            vec![WL_CLIPBOARD, XCLIP, XSEL]
+        } else if env::var_os("DISPLAY").is_some() {
+            vec![XCLIP, XSEL]
+        } else {
+            vec![]
+        };
+
+        candidates
+            .into_iter()
+            .find(|candidate| is_installed(candidate.copy[0]) && is_installed(candidate.paste[0]))
+    }
+
+    /// Runs `command` feeding it `input`, returning its output when `capture_output` is set.
+    ///
+    /// Copy tools fork a process that keeps serving the selection, so their output is never
+    /// captured: waiting for it to close would block until something else is copied.
+    fn run(&self, command: &[&str], input: &str, capture_output: bool) -> io::Result<Vec<u8>> {
+        let mut child = Command::new(command[0])
+            .args(&command[1..])
+            .stdin(Stdio::piped())
+            .stdout(if capture_output {
+                Stdio::piped()
+            } else {
+                Stdio::null()
+            })
+            .stderr(Stdio::null())
+            .spawn()?;
+
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(input.as_bytes())?;
+        }
+
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            return Err(io::Error::other(format!(
+                "{} exited with {}",
+                command[0], output.status
+            )));
+        }
+
+        Ok(output.stdout)
+    }
+}
+
+impl ClipboardBackend for CommandClipboard {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn copy(&mut self, text: &str) -> io::Result<()> {
+        self.run(self.copy, text, false).map(|_| ())
+    }
+
+    fn paste(&mut self) -> io::Result<Option<String>> {
+        let output = self.run(self.paste, "", true)?;
+
+        Ok(Some(String::from_utf8_lossy(&output).into_owned()))
+    }
+
+    fn clear(&mut self) -> io::Result<()> {
+        match self.clear {
+            Some(command) => self.run(command, "", false).map(|_| ()),
+            None => self.copy(""),
+        }
+    }
+}
+
+fn is_installed(program: &str) -> bool {
+    env::var_os("PATH").is_some_and(|paths| {
+        env::split_paths(&paths).any(|directory_path| directory_path.join(program).is_file())
+    })
+}
@@ -0,0 +1,37 @@
+use std::{cell::RefCell, io, rc::Rc};
+
+use super::ClipboardBackend;
+
+/// Keeps the clipboard in memory only, for tests and sessions without a usable clipboard.
+///
+/// Clones share their contents, so a copy kept aside sees what was put on the clipboard.
+#[derive(Clone, Default)]
+pub struct MemoryClipboard {
+    contents: Rc<RefCell<Option<String>>>,
+}
+
+impl MemoryClipboard {
+    pub fn contents(&self) -> Option<String> {
+        self.contents.borrow().clone()
+    }
+}
+
+impl ClipboardBackend for MemoryClipboard {
+    fn name(&self) -> &'static str {
+        "memory"
+    }
+
+    fn copy(&mut self, text: &str) -> io::Result<()> {
+        *self.contents.borrow_mut() = Some(text.to_string());
+        Ok(())
+    }
+
+    fn paste(&mut self) -> io::Result<Option<String>> {
+        Ok(Some(self.contents().unwrap_or_default()))
+    }
+
+    fn clear(&mut self) -> io::Result<()> {
+        *self.contents.borrow_mut() = None;
+        Ok(())
+    }
+}
@@ -0,0 +1,152 @@
+pub mod command;
+pub mod memory;
+pub mod osc52;
+#[cfg(windows)]
+pub mod win32;
+
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    io,
+    time::{Duration, Instant},
+};
+
+/// Seconds a copied secret stays on the clipboard unless configured otherwise.
+pub const DEFAULT_CLEAR_AFTER: Duration = Duration::from_secs(30);
+
+/// A way of putting text on the system clipboard.
+pub trait ClipboardBackend {
+    fn name(&self) -> &'static str;
+
+    fn copy(&mut self, text: &str) -> io::Result<()>;
+
+    /// Returns `Ok(None)` for backends that cannot read the clipboard back.
+    fn paste(&mut self) -> io::Result<Option<String>>;
+
+    fn clear(&mut self) -> io::Result<()>;
+}
+
+/// Picks the first backend usable in the current session, falling back to OSC 52,
+/// which works in most terminal emulators, including over SSH.
+pub fn detect() -> Box<dyn ClipboardBackend> {
+    #[cfg(windows)]
+    return Box::new(win32::Win32Clipboard);
+
+    #[cfg(not(windows))]
+    match command::CommandClipboard::detect() {
+        Some(backend) => Box::new(backend),
+        None => Box::new(osc52::Osc52Clipboard),
+    }
+}
+
+/// Copies secrets and takes them off the clipboard again once `clear_after` has passed.
+pub struct Clipboard {
+    backend: Box<dyn ClipboardBackend>,
+    clear_after: Option<Duration>,
+    pending_clear: Option<PendingClear>,
+}
+
+/// Only a fingerprint of the copied secret is kept, never the secret itself.
+struct PendingClear {
+    fingerprint: u64,
+    deadline: Instant,
+}
+
+impl Clipboard {
+    /// With `clear_after` set to `None` copied secrets are left on the clipboard.
+    pub fn new(backend: Box<dyn ClipboardBackend>, clear_after: Option<Duration>) -> Self {
+        Clipboard {
+            backend,
+            clear_after,
+            pending_clear: None,
+        }
+    }
+
+    pub fn backend_name(&self) -> &'static str {
+        self.backend.name()
+    }
+
+    pub fn copy_secret(&mut self, secret: &str) -> io::Result<()> {
+        self.backend.copy(secret)?;
+
+        self.pending_clear = self.clear_after.map(|clear_after| PendingClear {
+            fingerprint: fingerprint(secret),
+            deadline: Instant::now() + clear_after,
+        });
+
+        Ok(())
+    }
+
+    /// Clears the clipboard once the timeout of the last copied secret has passed. Meant to be
+    /// called on every tick of the main loop.
+    pub fn clear_if_expired(&mut self) -> io::Result<()> {
+        match &self.pending_clear {
+            Some(pending) if Instant::now() >= pending.deadline => self.clear_now(),
+            _ => Ok(()),
+        }
+    }
+
+    /// Clears the last copied secret right away, if it is still on the clipboard.
+    ///
+    /// Backends that cannot read the clipboard back are cleared unconditionally.
+    pub fn clear_now(&mut self) -> io::Result<()> {
+        let Some(pending) = self.pending_clear.take() else {
+            return Ok(());
+        };
+
+        match self.backend.paste()? {
+            Some(current) if fingerprint(&current) != pending.fingerprint => Ok(()),
+            _ => self.backend.clear(),
+        }
+    }
+}
+
+/// Takes a pending secret off the clipboard when the app exits, from whichever screen.
+impl Drop for Clipboard {
+    fn drop(&mut self) {
+        let _ = self.clear_now();
+    }
+}
+
+fn fingerprint(text: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    text.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use memory::MemoryClipboard;
+
+    #[test]
+    fn secrets_are_cleared_once_expired() {
+        let backend = MemoryClipboard::default();
+        let mut lasting = Clipboard::new(Box::new(backend.clone()), Some(Duration::from_secs(60)));
+
+        lasting.copy_secret("hunter2").unwrap();
+        lasting.clear_if_expired().unwrap();
+        assert_eq!(backend.contents().as_deref(), Some("hunter2"));
+
+        let mut expiring = Clipboard::new(Box::new(backend.clone()), Some(Duration::ZERO));
+        expiring.copy_secret("hunter3").unwrap();
+        expiring.clear_if_expired().unwrap();
+        assert_eq!(backend.contents(), None);
+    }
+
+    #[test]
+    fn only_our_own_secret_is_cleared() {
+        let backend = MemoryClipboard::default();
+        let mut clipboard = Clipboard::new(Box::new(backend.clone()), Some(Duration::ZERO));
+
+        clipboard.copy_secret("hunter2").unwrap();
+        // The user copied something else in the meantime.
+        backend.clone().copy("shopping list").unwrap();
+        clipboard.clear_if_expired().unwrap();
+        assert_eq!(backend.contents().as_deref(), Some("shopping list"));
+
+        clipboard.copy_secret("hunter2").unwrap();
+        drop(clipboard);
+        assert_eq!(backend.contents(), None);
+    }
+}
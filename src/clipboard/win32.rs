@@ -0,0 +1,107 @@
+use std::{
+    ffi::{c_char, CStr},
+    io,
+};
+
+use windows::Win32::{
+    Foundation::{GlobalFree, HANDLE, HGLOBAL},
+    System::{
+        DataExchange::{
+            CloseClipboard, EmptyClipboard, GetClipboardData, OpenClipboard, SetClipboardData,
+        },
+        Memory::{GlobalAlloc, GlobalLock, GlobalUnlock, GMEM_MOVEABLE},
+    },
+};
+
+use super::ClipboardBackend;
+
+const CF_TEXT: u32 = 1;
+
+/// The Win32 clipboard, holding text in the `CF_TEXT` format.
+pub struct Win32Clipboard;
+
+/// Keeps the clipboard open for as long as it is alive.
+struct OpenedClipboard;
+
+impl OpenedClipboard {
+    fn open() -> io::Result<Self> {
+        unsafe { OpenClipboard(None) }.map_err(to_io_error)?;
+        Ok(OpenedClipboard)
+    }
+}
+
+impl Drop for OpenedClipboard {
+    fn drop(&mut self) {
+        let _ = unsafe { CloseClipboard() };
+    }
+}
+
+impl ClipboardBackend for Win32Clipboard {
+    fn name(&self) -> &'static str {
+        "Windows clipboard"
+    }
+
+    fn copy(&mut self, text: &str) -> io::Result<()> {
+        let _clipboard = OpenedClipboard::open()?;
+
+        unsafe {
+            EmptyClipboard().map_err(to_io_error)?;
+
+            // Allocate global memory
+            let h_glob = GlobalAlloc(GMEM_MOVEABLE, text.len() + 1).map_err(to_io_error)?;
+            let p_glob = GlobalLock(h_glob);
+            if p_glob.is_null() {
+                let _ = GlobalFree(h_glob);
+                return Err(io::Error::last_os_error());
+            }
+
+            // Copy the text to the allocated memory
+            std::ptr::copy_nonoverlapping(text.as_ptr(), p_glob as *mut u8, text.len());
+            // Add null terminator
+            *((p_glob as *mut u8).add(text.len())) = 0;
+
+            let _ = GlobalUnlock(h_glob);
+
+            // The clipboard owns the memory once it accepted it, so it is only freed on failure.
+            if let Err(why) = SetClipboardData(CF_TEXT, HANDLE(h_glob.0)) {
+                let _ = GlobalFree(h_glob);
+                return Err(to_io_error(why));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn paste(&mut self) -> io::Result<Option<String>> {
+        let _clipboard = OpenedClipboard::open()?;
+
+        unsafe {
+            let Ok(handle) = GetClipboardData(CF_TEXT) else {
+                return Ok(Some(String::new()));
+            };
+
+            let h_glob = HGLOBAL(handle.0);
+            let p_glob = GlobalLock(h_glob);
+            if p_glob.is_null() {
+                return Err(io::Error::last_os_error());
+            }
+
+            let text = CStr::from_ptr(p_glob as *const c_char)
+                .to_string_lossy()
+                .into_owned();
+            let _ = GlobalUnlock(h_glob);
+
+            Ok(Some(text))
+        }
+    }
+
+    fn clear(&mut self) -> io::Result<()> {
+        let _clipboard = OpenedClipboard::open()?;
+
+        unsafe { EmptyClipboard() }.map_err(to_io_error)
+    }
+}
+
+fn to_io_error(why: windows::core::Error) -> io::Error {
+    io::Error::other(why.to_string())
+}
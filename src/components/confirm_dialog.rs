@@ -0,0 +1,46 @@
+use ratatui::prelude::*;
+use ratatui::widgets::{block::Title, Block, Clear, Padding, Paragraph, Widget, Wrap};
+use symbols::border;
+
+/// A modal asking the user to confirm a destructive action, drawn over the middle of `area`.
+pub struct ConfirmDialog<'a> {
+    pub title: &'a str,
+    pub question: String,
+}
+
+impl Widget for ConfirmDialog<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let width = area.width.min(60);
+        let height = area.height.min(7);
+        let popup = Rect::new(
+            area.x + (area.width - width) / 2,
+            area.y + (area.height - height) / 2,
+            width,
+            height,
+        );
+
+        let block = Block::bordered()
+            .title(Title::from(format!(" {} ", self.title).bold()).alignment(Alignment::Center))
+            .border_set(border::THICK)
+            .padding(Padding::horizontal(1))
+            .fg(Color::Yellow);
+
+        let text = Text::from(vec![
+            Line::from(self.question),
+            Line::default(),
+            Line::from(vec![
+                "<Y> ".bold(),
+                "Confirm / ".into(),
+                "<N> ".bold(),
+                "Cancel".into(),
+            ]),
+        ]);
+
+        Clear.render(popup, buf);
+        Paragraph::new(text)
+            .block(block)
+            .alignment(Alignment::Center)
+            .wrap(Wrap { trim: true })
+            .render(popup, buf);
+    }
+}
@@ -0,0 +1,232 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::prelude::*;
+
+use super::{
+    input_field::{InputField, InputFieldState},
+    strength_meter::{StrengthMeter, METER_HEIGHT},
+};
+use crate::strength;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum FormField {
+    Service,
+    Username,
+    Password,
+    Urls,
+    Tags,
+    Totp,
+    Notes,
+    CustomFields,
+    /// Not a field of an entry: what to import one-time passwords from.
+    OtpSource,
+}
+
+impl FormField {
+    fn input(self) -> InputField {
+        let mut input = InputField::default();
+
+        match self {
+            FormField::Service => input.label = "Service",
+            FormField::Username => input.label = "Username",
+            FormField::Password => {
+                input.label = "Password";
+                input.hide_value = true;
+            }
+            FormField::Urls => {
+                input.label = "URLs (comma separated)";
+                input.character_limit = 255;
+            }
+            FormField::Tags => input.label = "Tags (comma separated)",
+            FormField::Totp => {
+                input.label =
+                    "TOTP secret (, digits=6, period=30, algorithm=SHA1, counter= for HOTP)";
+                input.character_limit = 255;
+            }
+            FormField::Notes => {
+                input.label = "Notes";
+                input.multiline = true;
+                input.character_limit = 255;
+            }
+            FormField::CustomFields => {
+                input.label = "Custom fields (name = value, !name = secret)";
+                input.multiline = true;
+                input.character_limit = 255;
+            }
+            FormField::OtpSource => {
+                input.label = "otpauth:// URI, or path to a file with one per line";
+                // Migration exports grow with every account, cutting one short breaks it.
+                input.character_limit = u16::MAX;
+            }
+        }
+
+        input
+    }
+}
+
+/// The fields of an entry as a stack of inputs, one of them focused at a time.
+pub struct EntryForm {
+    inputs: Vec<(FormField, InputField)>,
+    active: usize,
+}
+
+impl EntryForm {
+    pub fn new(fields: &[FormField]) -> Self {
+        let mut form = EntryForm {
+            inputs: fields.iter().map(|field| (*field, field.input())).collect(),
+            active: 0,
+        };
+        form.focus(0);
+
+        form
+    }
+
+    pub fn value(&self, field: FormField) -> String {
+        self.input(field)
+            .map(InputField::get_value)
+            .unwrap_or_default()
+    }
+
+    pub fn set_value(&mut self, field: FormField, value: String) {
+        if let Some((_, input)) = self.inputs.iter_mut().find(|(f, _)| *f == field) {
+            input.set_value(value);
+        }
+    }
+
+    /// Empties every input, wiping hidden values from memory.
+    pub fn clear(&mut self) {
+        for (_, input) in self.inputs.iter_mut() {
+            input.clear_value();
+        }
+        self.focus(0);
+    }
+
+    pub fn focus(&mut self, index: usize) {
+        self.active = index.min(self.inputs.len().saturating_sub(1));
+
+        for (index, (_, input)) in self.inputs.iter_mut().enumerate() {
+            input.state = if index == self.active {
+                InputFieldState::Active
+            } else {
+                InputFieldState::Inactive
+            };
+        }
+    }
+
+    pub fn focus_next(&mut self) {
+        self.focus((self.active + 1) % self.inputs.len().max(1));
+    }
+
+    pub fn focus_previous(&mut self) {
+        let count = self.inputs.len().max(1);
+        self.focus((self.active + count - 1) % count);
+    }
+
+    /// Navigates between inputs and edits the focused one. <Alt+Enter> or <Ctrl+J> break
+    /// the line in multi-line inputs, a plain <Enter> is left to the caller.
+    pub fn handle_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Tab | KeyCode::Down => return self.focus_next(),
+            KeyCode::BackTab | KeyCode::Up => return self.focus_previous(),
+            _ => {}
+        }
+
+        let Some((_, input)) = self.inputs.get_mut(self.active) else {
+            return;
+        };
+
+        match key.code {
+            KeyCode::Enter if key.modifiers.contains(KeyModifiers::ALT) => input.add_line_break(),
+            KeyCode::Char('j') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                input.add_line_break()
+            }
+            KeyCode::Backspace => input.remove_character(),
+            KeyCode::Left => input.move_cursor_left(),
+            KeyCode::Right => input.move_cursor_right(),
+            KeyCode::Home => input.reset_cursor(),
+            KeyCode::End => input.place_cursor_at_end(),
+            KeyCode::Char(c) => input.add_character(c),
+            _ => {}
+        }
+    }
+
+    /// Where the terminal cursor belongs after the last render.
+    pub fn cursor_position(&self) -> Option<Position> {
+        self.inputs
+            .get(self.active)
+            .and_then(|(_, input)| input.cursor_position)
+    }
+
+    /// Lays single-line inputs out on the left and multi-line ones on the right, or
+    /// everything in one centered column when there are no multi-line inputs.
+    pub fn render(&mut self, area: Rect, buf: &mut Buffer) {
+        let has_multiline = self.inputs.iter().any(|(_, input)| input.multiline);
+
+        let columns = if has_multiline {
+            Layout::default()
+                .direction(Direction::Horizontal)
+                .horizontal_margin(2)
+                .spacing(2)
+                .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+                .split(area)
+        } else {
+            Layout::default()
+                .direction(Direction::Horizontal)
+                .horizontal_margin(20)
+                .constraints([Constraint::Min(1)])
+                .split(area)
+        };
+
+        let (multiline, single_line): (Vec<_>, Vec<_>) = self
+            .inputs
+            .iter_mut()
+            .partition(|(_, input)| input.multiline);
+
+        // The password gets a strength meter right under it.
+        let constraints = single_line.iter().map(|(field, _)| match field {
+            FormField::Password => Constraint::Length(4 + METER_HEIGHT),
+            _ => Constraint::Length(4),
+        });
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .flex(layout::Flex::Center)
+            .constraints(constraints)
+            .split(columns[0]);
+        for ((field, input), row) in single_line.into_iter().zip(rows.iter()) {
+            if *field != FormField::Password {
+                input.render(*row, buf);
+                continue;
+            }
+
+            let [input_area, meter_area] =
+                Layout::vertical([Constraint::Length(4), Constraint::Length(METER_HEIGHT)])
+                    .areas(*row);
+            input.render(input_area, buf);
+
+            let password = input.get_value();
+            if !password.is_empty() {
+                StrengthMeter {
+                    estimate: &strength::estimate(&password),
+                }
+                .render(meter_area, buf);
+            }
+        }
+
+        if let Some(column) = columns.get(1) {
+            let constraints = vec![Constraint::Fill(1); multiline.len()];
+            let rows = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints(constraints)
+                .split(*column);
+            for ((_, input), row) in multiline.into_iter().zip(rows.iter()) {
+                input.render(*row, buf);
+            }
+        }
+    }
+
+    fn input(&self, field: FormField) -> Option<&InputField> {
+        self.inputs
+            .iter()
+            .find(|(f, _)| *f == field)
+            .map(|(_, input)| input)
+    }
+}
@@ -0,0 +1,357 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Paragraph, Wrap},
+};
+use symbols::border;
+
+use super::input_field::{InputField, InputFieldState};
+use crate::generator::{
+    self,
+    passphrase::{Capitalization, MAX_WORDS},
+    GeneratorError, GeneratorMode, GeneratorOptions, MAX_LENGTH,
+};
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Row {
+    Service,
+    Mode,
+    Length,
+    Uppercase,
+    Lowercase,
+    Digits,
+    Symbols,
+    MinPerClass,
+    ExcludeAmbiguous,
+    Custom,
+    Words,
+    Capitalization,
+    AddDigit,
+    AddSymbol,
+    Separator,
+}
+
+const CHARACTER_ROWS: &[Row] = &[
+    Row::Service,
+    Row::Mode,
+    Row::Length,
+    Row::Uppercase,
+    Row::Lowercase,
+    Row::Digits,
+    Row::Symbols,
+    Row::MinPerClass,
+    Row::ExcludeAmbiguous,
+    Row::Custom,
+];
+
+const PASSPHRASE_ROWS: &[Row] = &[
+    Row::Service,
+    Row::Mode,
+    Row::Words,
+    Row::Capitalization,
+    Row::AddDigit,
+    Row::AddSymbol,
+    Row::Separator,
+];
+
+/// Asks for a service name and the generator options, previewing a password as they change.
+pub struct GeneratorDialog {
+    service: InputField,
+    custom: InputField,
+    separator: InputField,
+    options: GeneratorOptions,
+    focused: usize,
+    preview: Result<String, GeneratorError>,
+}
+
+impl GeneratorDialog {
+    pub fn new(options: GeneratorOptions) -> Self {
+        let mut service = InputField::default();
+        service.label = "Service";
+
+        let mut custom = InputField::default();
+        custom.label = "Custom characters";
+        custom.set_value(options.custom.clone());
+
+        let mut separator = InputField::default();
+        separator.label = "Separator";
+        separator.character_limit = 8;
+        separator.set_value(options.passphrase.separator.clone());
+
+        let mut dialog = GeneratorDialog {
+            service,
+            custom,
+            separator,
+            options,
+            focused: 0,
+            preview: Err(GeneratorError::EmptyLength),
+        };
+        dialog.focus(0);
+        dialog.regenerate();
+
+        dialog
+    }
+
+    pub fn service(&self) -> String {
+        self.service.get_value()
+    }
+
+    pub fn options(&self) -> &GeneratorOptions {
+        &self.options
+    }
+
+    /// The previewed password, which is what gets saved.
+    pub fn password(&self) -> Result<&str, &GeneratorError> {
+        self.preview.as_deref()
+    }
+
+    pub fn regenerate(&mut self) {
+        self.preview = generator::generate(&self.options, &mut rand::thread_rng());
+    }
+
+    /// Moves between rows with <Tab>/<Up>/<Down>, edits the focused input, flips options with
+    /// <Space> and changes numbers with <Left>/<Right>. <Ctrl+R> draws another password.
+    pub fn handle_key(&mut self, key: KeyEvent) {
+        let rows = self.rows();
+        match key.code {
+            KeyCode::Tab | KeyCode::Down => return self.focus((self.focused + 1) % rows.len()),
+            KeyCode::BackTab | KeyCode::Up => {
+                return self.focus((self.focused + rows.len() - 1) % rows.len())
+            }
+            KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                return self.regenerate()
+            }
+            _ => {}
+        }
+
+        let options = &mut self.options;
+        let passphrase = &mut options.passphrase;
+        let changed = match rows[self.focused] {
+            Row::Service => {
+                edit_input(&mut self.service, key.code);
+                false
+            }
+            Row::Custom => {
+                edit_input(&mut self.custom, key.code);
+                let custom = self.custom.get_value();
+                let changed = custom != options.custom;
+                options.custom = custom;
+                changed
+            }
+            Row::Separator => {
+                edit_input(&mut self.separator, key.code);
+                let separator = self.separator.get_value();
+                let changed = separator != passphrase.separator;
+                passphrase.separator = separator;
+                changed
+            }
+            Row::Mode => {
+                let mut passphrase_mode = options.mode == GeneratorMode::Passphrase;
+                let toggled = toggle(&mut passphrase_mode, key.code);
+                options.mode = if passphrase_mode {
+                    GeneratorMode::Passphrase
+                } else {
+                    GeneratorMode::Characters
+                };
+                toggled
+            }
+            Row::Capitalization => {
+                let all = Capitalization::ALL;
+                let index = all
+                    .iter()
+                    .position(|c| *c == passphrase.capitalization)
+                    .unwrap_or_default();
+                let index = match key.code {
+                    KeyCode::Left => (index + all.len() - 1) % all.len(),
+                    KeyCode::Right | KeyCode::Char(' ') => (index + 1) % all.len(),
+                    _ => index,
+                };
+                let changed = all[index] != passphrase.capitalization;
+                passphrase.capitalization = all[index];
+                changed
+            }
+            Row::Length => adjust(&mut options.length, 1..=MAX_LENGTH, key.code),
+            Row::MinPerClass => adjust(&mut options.min_per_class, 0..=MAX_LENGTH, key.code),
+            Row::Words => adjust(&mut passphrase.words, 1..=MAX_WORDS, key.code),
+            Row::Uppercase => toggle(&mut options.uppercase, key.code),
+            Row::Lowercase => toggle(&mut options.lowercase, key.code),
+            Row::Digits => toggle(&mut options.digits, key.code),
+            Row::Symbols => toggle(&mut options.symbols, key.code),
+            Row::ExcludeAmbiguous => toggle(&mut options.exclude_ambiguous, key.code),
+            Row::AddDigit => toggle(&mut passphrase.digit, key.code),
+            Row::AddSymbol => toggle(&mut passphrase.symbol, key.code),
+        };
+
+        if changed {
+            self.regenerate();
+        }
+    }
+
+    /// Where the terminal cursor belongs after the last render.
+    pub fn cursor_position(&self) -> Option<Position> {
+        match self.rows()[self.focused] {
+            Row::Service => self.service.cursor_position,
+            Row::Custom => self.custom.cursor_position,
+            Row::Separator => self.separator.cursor_position,
+            _ => None,
+        }
+    }
+
+    pub fn render(&mut self, area: Rect, buf: &mut Buffer) {
+        let rows = self.rows();
+        let option_rows = rows.len() as u16 - 2;
+        let parts = Layout::default()
+            .direction(Direction::Vertical)
+            .horizontal_margin(20)
+            .flex(layout::Flex::Center)
+            .constraints([
+                Constraint::Length(4),
+                Constraint::Length(option_rows + 1),
+                Constraint::Length(4),
+                Constraint::Length(4),
+            ])
+            .split(area);
+
+        self.service.render(parts[0], buf);
+
+        let lines: Vec<Line> = rows
+            .iter()
+            .enumerate()
+            .filter(|(_, row)| !is_input(**row))
+            .map(|(index, row)| self.option_line(*row, index == self.focused))
+            .collect();
+        Paragraph::new(lines).render(parts[1].inner(Margin::new(1, 0)), buf);
+
+        match self.options.mode {
+            GeneratorMode::Characters => self.custom.render(parts[2], buf),
+            GeneratorMode::Passphrase => self.separator.render(parts[2], buf),
+        }
+
+        let preview = match &self.preview {
+            Ok(password) => Text::from(password.as_str().green().bold()),
+            Err(why) => Text::from(why.to_string().red()),
+        };
+        let title = format!(
+            " Preview, {:.0} bits of entropy ",
+            generator::entropy_bits(&self.options)
+        );
+        Paragraph::new(preview)
+            .block(Block::bordered().title(title).border_set(border::ROUNDED))
+            .wrap(Wrap { trim: false })
+            .render(parts[3], buf);
+    }
+
+    fn rows(&self) -> &'static [Row] {
+        match self.options.mode {
+            GeneratorMode::Characters => CHARACTER_ROWS,
+            GeneratorMode::Passphrase => PASSPHRASE_ROWS,
+        }
+    }
+
+    fn option_line(&self, row: Row, focused: bool) -> Line<'static> {
+        let checkbox = |enabled: bool| String::from(if enabled { "[x]" } else { "[ ]" });
+        let options = &self.options;
+        let passphrase = &options.passphrase;
+        let (name, value) = match row {
+            Row::Mode => (
+                "Mode",
+                match options.mode {
+                    GeneratorMode::Characters => String::from("< characters >"),
+                    GeneratorMode::Passphrase => String::from("< passphrase >"),
+                },
+            ),
+            Row::Length => ("Length", format!("< {} >", options.length)),
+            Row::Uppercase => ("Uppercase A-Z", checkbox(options.uppercase)),
+            Row::Lowercase => ("Lowercase a-z", checkbox(options.lowercase)),
+            Row::Digits => ("Digits 0-9", checkbox(options.digits)),
+            Row::Symbols => ("Symbols !@#...", checkbox(options.symbols)),
+            Row::MinPerClass => (
+                "Minimum per class",
+                format!("< {} >", options.min_per_class),
+            ),
+            Row::ExcludeAmbiguous => (
+                "Exclude ambiguous (0O1lI...)",
+                checkbox(options.exclude_ambiguous),
+            ),
+            Row::Words => ("Words", format!("< {} >", passphrase.words)),
+            Row::Capitalization => (
+                "Capitalization",
+                format!("< {} >", passphrase.capitalization.name()),
+            ),
+            Row::AddDigit => ("Add a digit", checkbox(passphrase.digit)),
+            Row::AddSymbol => ("Add a symbol", checkbox(passphrase.symbol)),
+            Row::Service | Row::Custom | Row::Separator => ("", String::new()),
+        };
+
+        let line = Line::from(vec![
+            if focused { "> " } else { "  " }.into(),
+            format!("{name:<30}").into(),
+            value.into(),
+        ]);
+
+        if focused {
+            line.bold()
+        } else {
+            line
+        }
+    }
+
+    fn focus(&mut self, index: usize) {
+        self.focused = index;
+        let focused = self.rows()[index];
+
+        for (row, input) in [
+            (Row::Service, &mut self.service),
+            (Row::Custom, &mut self.custom),
+            (Row::Separator, &mut self.separator),
+        ] {
+            input.state = if focused == row {
+                InputFieldState::Active
+            } else {
+                InputFieldState::Inactive
+            };
+        }
+    }
+}
+
+fn is_input(row: Row) -> bool {
+    matches!(row, Row::Service | Row::Custom | Row::Separator)
+}
+
+fn edit_input(input: &mut InputField, key_code: KeyCode) {
+    match key_code {
+        KeyCode::Backspace => input.remove_character(),
+        KeyCode::Left => input.move_cursor_left(),
+        KeyCode::Right => input.move_cursor_right(),
+        KeyCode::Home => input.reset_cursor(),
+        KeyCode::End => input.place_cursor_at_end(),
+        KeyCode::Char(c) => input.add_character(c),
+        _ => {}
+    }
+}
+
+/// Flips `value` with <Space>, <Left> or <Right>. Returns whether it changed.
+fn toggle(value: &mut bool, key_code: KeyCode) -> bool {
+    match key_code {
+        KeyCode::Char(' ') | KeyCode::Left | KeyCode::Right => {
+            *value = !*value;
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Steps `value` down with <Left> or `-` and up with <Right> or `+`, within `range`.
+/// Returns whether it changed.
+fn adjust(value: &mut usize, range: std::ops::RangeInclusive<usize>, key_code: KeyCode) -> bool {
+    let adjusted = match key_code {
+        KeyCode::Left | KeyCode::Char('-') => value.saturating_sub(1),
+        KeyCode::Right | KeyCode::Char('+') => value.saturating_add(1),
+        _ => return false,
+    };
+    let adjusted = adjusted.clamp(*range.start(), *range.end());
+
+    let changed = adjusted != *value;
+    *value = adjusted;
+    changed
+}
@@ -3,6 +3,10 @@ use ratatui::widgets::{Block, Padding, Paragraph, Widget};
 use secrecy::zeroize::Zeroize;
 use symbols::border;
 
+/// Characters a master password may have: more than the generator's longest password or
+/// passphrase, which are what people are told to use.
+pub const MASTER_PASSWORD_LIMIT: u16 = 256;
+
 #[derive(PartialEq, Copy, Clone)]
 pub enum InputFieldState {
     Active,
@@ -15,8 +19,12 @@ pub struct InputField {
     value: String,
     pub label: &'static str,
     pub hide_value: bool,
+    /// Lets the value span several lines, broken with [`InputField::add_line_break`].
+    pub multiline: bool,
     pub state: InputFieldState,
-    pub character_limit: u8,
+    /// Most characters that can be typed in; a longer value set with
+    /// [`InputField::set_value`] raises it.
+    pub character_limit: u16,
     pub cursor_position: Option<Position>,
     default_cursor_position: Position,
     cursor_index: usize,
@@ -24,19 +32,18 @@ pub struct InputField {
 
 impl Default for InputField {
     fn default() -> Self {
-        let character_limit: u8 = 32;
-        let field = InputField {
+        let character_limit: u16 = 32;
+        InputField {
             label: "",
             hide_value: false,
+            multiline: false,
             state: InputFieldState::Inactive,
             character_limit,
             value: String::with_capacity(character_limit.into()),
             cursor_position: None,
             cursor_index: 0,
             default_cursor_position: Default::default(),
-        };
-
-        field
+        }
     }
 }
 
@@ -48,9 +55,23 @@ impl Widget for &mut InputField {
             .border_set(border::ROUNDED)
             .title(label);
 
-        if self.cursor_position.is_none() && self.state == InputFieldState::Active {
-            self.cursor_position = Some(Position::new(area.x + 3, area.y + 2));
+        if self.state == InputFieldState::Active {
             self.default_cursor_position = Position::new(area.x + 3, area.y + 2);
+
+            let before_cursor: String = self.value.chars().take(self.cursor_index).collect();
+            let row = before_cursor.matches('\n').count() as u16;
+            let column = before_cursor
+                .rsplit('\n')
+                .next()
+                .unwrap_or_default()
+                .chars()
+                .count() as u16;
+            self.cursor_position = Some(Position::new(
+                self.default_cursor_position.x.saturating_add(column),
+                self.default_cursor_position.y.saturating_add(row),
+            ));
+        } else {
+            self.cursor_position = None;
         }
 
         let display_value = if self.hide_value {
@@ -70,26 +91,41 @@ impl Widget for &mut InputField {
 
 impl InputField {
     pub fn clear_value(&mut self) {
-        if self.hide_value {
-            self.value.zeroize();
-        }
-        self.value.clear();
+        // Any field may have held something secret, e.g. a TOTP secret or a custom field.
+        self.value.zeroize();
         self.cursor_index = 0;
         self.cursor_position = None;
     }
 
+    /// Replaces the value, e.g. to prefill the field, and puts the cursor after it. The limit
+    /// grows to fit the value, so an edited value can always be typed back in full.
+    pub fn set_value(&mut self, value: String) {
+        self.clear_value();
+        self.value = value;
+        self.cursor_index = self.value.chars().count();
+
+        let length = u16::try_from(self.cursor_index).unwrap_or(u16::MAX);
+        self.character_limit = self.character_limit.max(length);
+    }
+
     pub fn get_value(&self) -> String {
         self.value.clone()
     }
 
     pub fn add_character(&mut self, new_char: char) {
-        if self.value.len() < self.character_limit.into() {
+        if self.value.chars().count() < self.character_limit.into() {
             let index = self.byte_index();
             self.value.insert(index, new_char);
             self.move_cursor_right();
         }
     }
 
+    pub fn add_line_break(&mut self) {
+        if self.multiline {
+            self.add_character('\n');
+        }
+    }
+
     pub fn remove_character(&mut self) {
         let is_not_cursor_leftmost = self.cursor_index != 0;
         if is_not_cursor_leftmost {
@@ -113,10 +149,14 @@ impl InputField {
     }
 
     pub fn move_cursor_right(&mut self) {
-        if self.cursor_position.is_some() && self.cursor_index < self.value.len() {
+        if self.cursor_position.is_some() && self.cursor_index < self.value.chars().count() {
             let position = self.cursor_position.unwrap();
             self.cursor_position = Some(Position::new(
-                (position.x + 1).min(self.default_cursor_position.x + self.character_limit as u16),
+                position.x.saturating_add(1).min(
+                    self.default_cursor_position
+                        .x
+                        .saturating_add(self.character_limit),
+                ),
                 position.y,
             ));
             let index = self.cursor_index.saturating_add(1);
@@ -152,7 +192,7 @@ impl InputField {
             // Attempt to convert end_position to u16
             if let Ok(end_u16) = end_position.try_into() {
                 self.cursor_position = Some(Position::new(end_u16, position.y));
-                self.cursor_index = self.value.len();
+                self.cursor_index = self.value.chars().count();
             }
         }
     }
@@ -169,3 +209,40 @@ impl InputField {
             .unwrap_or(self.value.len())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An input as it is after being rendered, which is when the cursor starts to move.
+    fn rendered_input() -> InputField {
+        InputField {
+            cursor_position: Some(Position::default()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn the_limit_counts_characters() {
+        let mut input = rendered_input();
+        input.character_limit = 4;
+
+        for c in "ééééé".chars() {
+            input.add_character(c);
+        }
+
+        assert_eq!(input.get_value(), "éééé");
+    }
+
+    #[test]
+    fn values_longer_than_the_limit_can_be_edited() {
+        let mut input = rendered_input();
+        let long = "x".repeat(40);
+
+        input.set_value(long.clone());
+        input.remove_character();
+        input.add_character('y');
+
+        assert_eq!(input.get_value(), format!("{}y", &long[1..]));
+    }
+}
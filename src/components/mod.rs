@@ -1,2 +1,6 @@
+pub mod confirm_dialog;
+pub mod entry_form;
+pub mod generator_dialog;
 pub mod input_field;
 pub mod password_list;
+pub mod strength_meter;
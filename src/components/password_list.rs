@@ -1,57 +1,347 @@
 use ratatui::{
-    text::Line,
+    style::{Style, Stylize},
+    text::{Line, Span},
     widgets::{ListItem, ListState},
 };
 
+use crate::{fuzzy, vault::entry::Entry};
+
 pub struct PasswordListItem {
-    pub label: String,
-    pub encrypted_value: String,
+    pub entry: Entry,
+    /// Set when the value failed to decrypt, e.g. because the vault was tampered with.
+    pub is_corrupted: bool,
 }
 
-impl From<(String, String)> for PasswordListItem {
-    fn from(value: (String, String)) -> Self {
+impl From<Entry> for PasswordListItem {
+    fn from(value: Entry) -> Self {
         PasswordListItem {
-            label: value.0.clone(),
-            encrypted_value: value.1.clone(),
+            entry: value,
+            is_corrupted: false,
         }
     }
 }
 
-impl From<(&str, &str)> for PasswordListItem {
-    fn from(value: (&str, &str)) -> Self {
-        PasswordListItem {
-            label: value.0.to_string(),
-            encrypted_value: value.1.to_string(),
+impl PasswordListItem {
+    /// The row for this item, with the characters matched by `hit` highlighted.
+    fn list_item(&self, hit: Option<&SearchHit>) -> ListItem<'static> {
+        let entry = &self.entry;
+        let positions = |field: SearchField| match hit {
+            Some(hit) if hit.field == field => hit.positions.as_slice(),
+            _ => &[],
+        };
+
+        let mut spans = highlighted(&entry.label, positions(SearchField::Label), Style::new());
+
+        if !entry.username.is_empty() {
+            let style = Style::new().dark_gray();
+            spans.push(Span::styled(" (", style));
+            spans.extend(highlighted(
+                &entry.username,
+                positions(SearchField::Username),
+                style,
+            ));
+            spans.push(Span::styled(")", style));
+        }
+        for (index, tag) in entry.tags.iter().enumerate() {
+            let style = Style::new().italic();
+            spans.push(Span::styled(" #", style));
+            spans.extend(highlighted(tag, positions(SearchField::Tag(index)), style));
         }
+        // URLs are only shown when they are why the entry matched.
+        if let Some(SearchField::Url(index)) = hit.map(|hit| hit.field) {
+            let style = Style::new().dark_gray();
+            spans.push(Span::styled(" ", style));
+            spans.extend(highlighted(
+                &entry.urls[index],
+                positions(SearchField::Url(index)),
+                style,
+            ));
+        }
+        if entry.strength.is_some_and(|score| score <= 1) {
+            spans.push(" (weak)".yellow());
+        }
+        if self.is_corrupted {
+            spans.push(" (corrupted)".red().bold());
+        }
+
+        ListItem::new(Line::from(spans))
     }
 }
 
-impl From<&(&str, &str)> for PasswordListItem {
-    fn from(value: &(&str, &str)) -> Self {
-        PasswordListItem {
-            label: value.0.to_string(),
-            encrypted_value: value.1.to_string(),
-        }
-    }
+/// The part of an entry a search matched.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SearchField {
+    Label,
+    Username,
+    /// The URL at this index of the entry.
+    Url(usize),
+    /// The tag at this index of the entry.
+    Tag(usize),
 }
 
-impl From<&PasswordListItem> for ListItem<'static> {
-    fn from(value: &PasswordListItem) -> Self {
-        ListItem::new(Line::from(value.label.to_string()))
-    }
+/// An item matching the search.
+pub struct SearchHit {
+    /// Index of the item in [`PasswordList::items`].
+    pub index: usize,
+    pub field: SearchField,
+    score: i64,
+    /// Character indices of the matched characters in the field.
+    positions: Vec<usize>,
+}
+
+struct Search {
+    query: String,
+    /// Best match first.
+    hits: Vec<SearchHit>,
 }
 
+/// A list of entries, possibly narrowed down by a search.
+///
+/// While a search is going on, `state` selects among the matching items only, so use
+/// [`PasswordList::selected`] to find the selected item.
 pub struct PasswordList {
     pub items: Vec<PasswordListItem>,
     pub state: ListState,
+    search: Option<Search>,
 }
 
-impl From<Vec<(&str, &str)>> for PasswordList {
-    fn from(value: Vec<(&str, &str)>) -> Self {
-        let items: Vec<PasswordListItem> = value.iter().map(PasswordListItem::from).collect();
+impl From<Vec<Entry>> for PasswordList {
+    fn from(value: Vec<Entry>) -> Self {
+        let items: Vec<PasswordListItem> = value.into_iter().map(PasswordListItem::from).collect();
         PasswordList {
             items,
             state: ListState::default(),
+            search: None,
+        }
+    }
+}
+
+impl PasswordList {
+    /// Index in `items` of the selected item, if any.
+    pub fn selected(&self) -> Option<usize> {
+        let row = self.state.selected()?;
+        match &self.search {
+            Some(search) => search.hits.get(row).map(|hit| hit.index),
+            None => (row < self.items.len()).then_some(row),
+        }
+    }
+
+    /// The query narrowing the list down, if any.
+    pub fn query(&self) -> Option<&str> {
+        self.search.as_ref().map(|search| search.query.as_str())
+    }
+
+    /// How many items are shown.
+    pub fn shown_count(&self) -> usize {
+        self.search
+            .as_ref()
+            .map_or(self.items.len(), |search| search.hits.len())
+    }
+
+    /// The rows to render, in order.
+    pub fn list_items(&self) -> Vec<ListItem<'static>> {
+        match &self.search {
+            Some(search) => search
+                .hits
+                .iter()
+                .map(|hit| self.items[hit.index].list_item(Some(hit)))
+                .collect(),
+            None => self.items.iter().map(|item| item.list_item(None)).collect(),
+        }
+    }
+
+    /// Shows only the items fuzzy matching `query`, best first, and selects the best one.
+    pub fn search(&mut self, query: &str) {
+        self.search = Some(Search {
+            query: query.to_string(),
+            hits: vec![],
+        });
+        self.refresh_search();
+        self.select_row(0);
+    }
+
+    /// Shows every item again, keeping the selected one selected.
+    pub fn clear_search(&mut self) {
+        let selected = self.selected();
+        self.search = None;
+
+        match selected {
+            Some(index) => self.state.select(Some(index)),
+            None => self.select_row(0),
+        }
+    }
+
+    /// Selects `row`, the last row if there are fewer, or nothing if none are shown.
+    pub fn select_row(&mut self, row: usize) {
+        let selected = match self.shown_count() {
+            0 => None,
+            count => Some(row.min(count - 1)),
+        };
+
+        self.state.select(selected);
+    }
+
+    /// Selects the item at `index` of `items`, if it is shown.
+    pub fn select_item(&mut self, index: usize) {
+        let row = match &self.search {
+            Some(search) => search.hits.iter().position(|hit| hit.index == index),
+            None => (index < self.items.len()).then_some(index),
+        };
+
+        if row.is_some() {
+            self.state.select(row);
         }
     }
+
+    /// Adds `item` at the end, keeping the selected item selected.
+    pub fn push(&mut self, item: PasswordListItem) {
+        let selected = self.selected();
+        self.items.push(item);
+        self.refresh_search();
+
+        if let Some(index) = selected {
+            self.select_item(index);
+        }
+    }
+
+    /// Puts `item` back at `index` and selects it.
+    pub fn insert(&mut self, index: usize, item: PasswordListItem) {
+        self.items.insert(index, item);
+        self.refresh_search();
+        self.select_item(index);
+    }
+
+    /// Takes the item at `index` out, leaving the selection on the same row.
+    pub fn remove(&mut self, index: usize) -> PasswordListItem {
+        let row = self.state.selected().unwrap_or_default();
+        let item = self.items.remove(index);
+        self.refresh_search();
+        self.select_row(row);
+
+        item
+    }
+
+    /// Matches the item at `index` against the search again after it was edited, keeping it
+    /// selected if it still matches.
+    pub fn edited(&mut self, index: usize) {
+        let row = self.state.selected().unwrap_or_default();
+        self.refresh_search();
+        self.select_row(row);
+        self.select_item(index);
+    }
+
+    fn refresh_search(&mut self) {
+        let Some(search) = self.search.as_mut() else {
+            return;
+        };
+
+        search.hits = self
+            .items
+            .iter()
+            .enumerate()
+            .filter_map(|(index, item)| best_hit(index, &item.entry, &search.query))
+            .collect();
+        // Stable, so equally good matches keep the order of the list.
+        search.hits.sort_by_key(|hit| std::cmp::Reverse(hit.score));
+    }
+}
+
+/// The best match of `query` among the searchable fields of `entry`, the label winning ties.
+fn best_hit(index: usize, entry: &Entry, query: &str) -> Option<SearchHit> {
+    let fields = [
+        (SearchField::Label, entry.label.as_str()),
+        (SearchField::Username, entry.username.as_str()),
+    ];
+    let urls = entry.urls.iter().enumerate();
+    let tags = entry.tags.iter().enumerate();
+
+    fields
+        .into_iter()
+        .chain(urls.map(|(i, url)| (SearchField::Url(i), url.as_str())))
+        .chain(tags.map(|(i, tag)| (SearchField::Tag(i), tag.as_str())))
+        .filter_map(|(field, text)| {
+            fuzzy::fuzzy_match(query, text).map(|found| SearchHit {
+                index,
+                field,
+                score: found.score,
+                positions: found.positions,
+            })
+        })
+        .reduce(|best, hit| if hit.score > best.score { hit } else { best })
+}
+
+/// `text` in `style`, with the characters at `positions` standing out.
+fn highlighted(text: &str, positions: &[usize], style: Style) -> Vec<Span<'static>> {
+    let matched = style.yellow().bold().underlined();
+    let mut spans: Vec<Span> = vec![];
+
+    for (index, c) in text.chars().enumerate() {
+        let style = if positions.contains(&index) {
+            matched
+        } else {
+            style
+        };
+
+        match spans.last_mut() {
+            Some(last) if last.style == style => last.content.to_mut().push(c),
+            _ => spans.push(Span::styled(c.to_string(), style)),
+        }
+    }
+
+    spans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list(labels: &[&str]) -> PasswordList {
+        let entries: Vec<Entry> = labels
+            .iter()
+            .map(|label| Entry::new(label.to_string(), String::new()))
+            .collect();
+        PasswordList::from(entries)
+    }
+
+    #[test]
+    fn search_selects_the_best_match_and_maps_rows_to_items() {
+        let mut list = list(&["Digital Ocean", "Mail", "GitHub", "Gitea"]);
+        list.items[1].entry.urls = vec![String::from("https://git.example.com")];
+
+        list.search("git");
+
+        assert_eq!(list.shown_count(), 4);
+        assert_eq!(list.selected(), Some(2));
+        assert_eq!(list.query(), Some("git"));
+
+        list.search("gith");
+        assert_eq!(list.shown_count(), 1);
+        assert_eq!(list.selected(), Some(2));
+
+        list.search("nothing like it");
+        assert_eq!(list.selected(), None);
+    }
+
+    #[test]
+    fn changes_keep_the_search_up_to_date() {
+        let mut list = list(&["Bank", "Mail", "Bakery"]);
+        list.search("ba");
+        list.state.select(Some(1));
+        let selected = list.selected().unwrap();
+
+        let removed = list.remove(selected);
+        assert_eq!(list.shown_count(), 1);
+        assert_eq!(
+            list.selected().map(|i| &list.items[i].entry.label),
+            Some(&String::from("Bank"))
+        );
+
+        list.insert(selected, removed);
+        assert_eq!(list.shown_count(), 2);
+        assert_eq!(list.selected(), Some(selected));
+
+        list.clear_search();
+        assert_eq!(list.shown_count(), 3);
+        assert_eq!(list.selected(), Some(selected));
+    }
 }
@@ -0,0 +1,58 @@
+use ratatui::prelude::*;
+use ratatui::widgets::{Paragraph, Widget};
+
+use crate::strength::{Estimate, MAX_SCORE};
+
+/// Rows a [`StrengthMeter`] needs: the bar, then the warning or first suggestion.
+pub const METER_HEIGHT: u16 = 2;
+
+/// A bar colored by score, with the crack time and the main piece of feedback below it.
+pub struct StrengthMeter<'a> {
+    pub estimate: &'a Estimate,
+}
+
+pub fn score_color(score: u8) -> Color {
+    match score {
+        0 => Color::Red,
+        1 => Color::LightRed,
+        2 => Color::Yellow,
+        3 => Color::LightGreen,
+        _ => Color::Green,
+    }
+}
+
+impl Widget for StrengthMeter<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let estimate = self.estimate;
+        let color = score_color(estimate.score);
+
+        let segments = usize::from(MAX_SCORE) + 1;
+        let filled = usize::from(estimate.score) + 1;
+        let bar = vec!["■■■ "; segments]
+            .into_iter()
+            .enumerate()
+            .map(|(index, segment)| {
+                if index < filled {
+                    Span::from(segment).fg(color)
+                } else {
+                    Span::from(segment).dark_gray()
+                }
+            });
+
+        let mut summary: Vec<Span> = bar.collect();
+        summary.push(estimate.label().bold().fg(color));
+        summary.push(format!(", cracked in {}", estimate.crack_time()).into());
+
+        let hint = estimate
+            .warning
+            .or_else(|| estimate.suggestions.first().copied())
+            .unwrap_or_default();
+
+        Paragraph::new(vec![
+            Line::from(summary),
+            Line::from(hint.italic().fg(color)),
+        ])
+        .alignment(Alignment::Center)
+        .render(area, buf);
+    }
+}
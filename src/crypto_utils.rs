@@ -1,44 +1,144 @@
 use core::str;
+use std::fmt;
 
 use argon2::{Algorithm, Argon2, Params, Version};
 use chacha20poly1305::aead::generic_array::typenum::Unsigned;
 use chacha20poly1305::aead::generic_array::GenericArray;
-use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
 use chacha20poly1305::ChaCha20Poly1305;
+use rand::RngCore;
+use secrecy::{zeroize::Zeroizing, ExposeSecret, SecretString};
 
-pub fn encrypt(cleartext: &str, key: &[u8]) -> Vec<u8> {
-    let cipher = ChaCha20Poly1305::new(GenericArray::from_slice(key));
+pub const SALT_LENGTH: usize = 16;
+
+/// Everything needed to re-derive a master key from a password.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KdfParams {
+    pub algorithm: Algorithm,
+    pub version: Version,
+    pub m_cost: u32,
+    pub t_cost: u32,
+    pub p_cost: u32,
+    pub salt: [u8; SALT_LENGTH],
+}
+
+impl KdfParams {
+    /// Argon2 defaults with a fresh random salt, used for newly created vaults.
+    pub fn generate() -> Self {
+        let mut salt = [0u8; SALT_LENGTH];
+        OsRng.fill_bytes(&mut salt);
+
+        KdfParams {
+            algorithm: Algorithm::Argon2id,
+            version: Version::V0x13,
+            m_cost: Params::DEFAULT_M_COST,
+            t_cost: Params::DEFAULT_T_COST,
+            p_cost: Params::DEFAULT_P_COST,
+            salt,
+        }
+    }
+
+    /// The fixed parameters every vault was derived with before headers existed.
+    pub fn legacy() -> Self {
+        KdfParams {
+            algorithm: Algorithm::Argon2id,
+            version: Version::V0x13,
+            m_cost: 15000,
+            t_cost: 2,
+            p_cost: 1,
+            salt: [0x02; SALT_LENGTH],
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum CryptoError {
+    InvalidKey,
+    Encryption,
+    /// The ciphertext was truncated, tampered with, or sealed under another key.
+    Decryption,
+    Encoding,
+    KeyDerivation(argon2::Error),
+}
+
+impl fmt::Display for CryptoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CryptoError::InvalidKey => write!(f, "the key has an invalid length"),
+            CryptoError::Encryption => write!(f, "encryption failed"),
+            CryptoError::Decryption => write!(f, "the data is corrupted or was tampered with"),
+            CryptoError::Encoding => write!(f, "the data is not validly encoded"),
+            CryptoError::KeyDerivation(why) => write!(f, "key derivation failed: {why}"),
+        }
+    }
+}
+
+impl From<argon2::Error> for CryptoError {
+    fn from(value: argon2::Error) -> Self {
+        CryptoError::KeyDerivation(value)
+    }
+}
+
+fn cipher(key: &[u8]) -> Result<ChaCha20Poly1305, CryptoError> {
+    ChaCha20Poly1305::new_from_slice(key).map_err(|_| CryptoError::InvalidKey)
+}
+
+pub fn encrypt(cleartext: &str, key: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    seal(cleartext.as_bytes(), &[], key)
+}
+
+pub fn decrypt(obsf: &[u8], key: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    open(obsf, &[], key)
+}
+
+/// Encrypts `cleartext` and authenticates it together with `associated_data`,
+/// which is not stored in the output and must be supplied again to [`open`].
+pub fn seal(cleartext: &[u8], associated_data: &[u8], key: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    let cipher = cipher(key)?;
     let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
-    let mut obsf = cipher.encrypt(&nonce, cleartext.as_bytes()).unwrap();
+    let payload = Payload {
+        msg: cleartext,
+        aad: associated_data,
+    };
+    let mut obsf = cipher
+        .encrypt(&nonce, payload)
+        .map_err(|_| CryptoError::Encryption)?;
     obsf.splice(..0, nonce.iter().copied());
 
-    obsf
+    Ok(obsf)
 }
 
-pub fn decrypt(obsf: &[u8], key: &[u8]) -> Vec<u8> {
+pub fn open(obsf: &[u8], associated_data: &[u8], key: &[u8]) -> Result<Vec<u8>, CryptoError> {
     type NonceSize = <ChaCha20Poly1305 as AeadCore>::NonceSize;
-    let cipher = ChaCha20Poly1305::new(GenericArray::from_slice(key));
+    if obsf.len() < NonceSize::to_usize() {
+        return Err(CryptoError::Decryption);
+    }
+
+    let cipher = cipher(key)?;
     let (nonce, ciphertext) = obsf.split_at(NonceSize::to_usize());
     let nonce = GenericArray::from_slice(nonce);
+    let payload = Payload {
+        msg: ciphertext,
+        aad: associated_data,
+    };
 
-    match cipher.decrypt(nonce, ciphertext) {
-        Ok(value) => value,
-        Err(why) => panic!("{}", why),
-    }
+    cipher
+        .decrypt(nonce, payload)
+        .map_err(|_| CryptoError::Decryption)
 }
 
-pub fn hash_password(password: String) -> [u8; 32] {
+pub fn hash_password(
+    password: &SecretString,
+    kdf: &KdfParams,
+) -> Result<Zeroizing<[u8; 32]>, CryptoError> {
     let hasher = Argon2::new(
-        Algorithm::Argon2id,
-        Version::V0x13,
-        Params::new(15000, 2, 1, None).expect("Cannot build hasher params"),
+        kdf.algorithm,
+        kdf.version,
+        Params::new(kdf.m_cost, kdf.t_cost, kdf.p_cost, None)?,
     );
 
-    let mut out = [0u8; 32];
-    let salt = [0x02; 16];
-    hasher
-        .hash_password_into(password.as_bytes(), &salt, &mut out)
-        .expect("Failed to hash the password");
+    let mut out = Zeroizing::new([0u8; 32]);
+    hasher.hash_password_into(password.expose_secret().as_bytes(), &kdf.salt, &mut *out)?;
 
-    out
+    Ok(out)
 }
@@ -0,0 +1,163 @@
+use std::{
+    env,
+    ffi::OsString,
+    fs,
+    io::{self, ErrorKind},
+    path::{Path, PathBuf},
+};
+
+const APP_DIRECTORY: &str = "rusty-lock";
+/// Where versions before the data directory kept their vaults, relative to the working
+/// directory.
+const LEGACY_VAULTS_DIRECTORY: &str = "pwds";
+
+/// Environment variable overriding the application home, where vaults are kept.
+pub const HOME_VARIABLE: &str = "RUSTY_LOCK_HOME";
+
+/// Resolves where vault files are kept, in order of precedence:
+///
+/// 1. `vault_dir_override`, as passed with `--vault-dir`
+/// 2. `$RUSTY_LOCK_HOME`
+/// 3. `<platform data directory>/rusty-lock`
+pub fn vault_directory(vault_dir_override: Option<PathBuf>) -> io::Result<PathBuf> {
+    resolve_vault_directory(vault_dir_override, |name| env::var_os(name))
+}
+
+/// Whether `directory` is the one the application keeps in the platform data directory. Any
+/// other was chosen by the user and may hold files of their own.
+pub fn is_application_directory(directory: &Path) -> bool {
+    application_directory(&|name| env::var_os(name)).is_some_and(|own| own == directory)
+}
+
+/// Where versions before the data directory kept their vaults, if there is something there
+/// other than `vault_directory`.
+pub fn legacy_vault_directory(vault_directory: &Path) -> Option<PathBuf> {
+    let legacy = PathBuf::from(LEGACY_VAULTS_DIRECTORY);
+    let is_elsewhere = match (legacy.canonicalize(), vault_directory.canonicalize()) {
+        (Ok(legacy), Ok(current)) => legacy != current,
+        (Ok(_), Err(_)) => true,
+        (Err(_), _) => false,
+    };
+
+    let has_files = fs::read_dir(&legacy).is_ok_and(|mut entries| entries.next().is_some());
+
+    (has_files && is_elsewhere).then_some(legacy)
+}
+
+/// [`vault_directory`] with environment variables read through `variable`.
+fn resolve_vault_directory(
+    vault_dir_override: Option<PathBuf>,
+    variable: impl Fn(&str) -> Option<OsString>,
+) -> io::Result<PathBuf> {
+    if let Some(directory_path) = vault_dir_override {
+        return Ok(directory_path);
+    }
+
+    match variable(HOME_VARIABLE).filter(|home| !home.is_empty()) {
+        Some(home) => Ok(PathBuf::from(home)),
+        None => application_directory(&variable).ok_or_else(|| {
+            io::Error::new(
+                ErrorKind::NotFound,
+                format!(
+                    "couldn't determine a data directory, set {HOME_VARIABLE} or pass --vault-dir"
+                ),
+            )
+        }),
+    }
+}
+
+fn application_directory(variable: &impl Fn(&str) -> Option<OsString>) -> Option<PathBuf> {
+    platform_data_directory(variable).map(|directory_path| directory_path.join(APP_DIRECTORY))
+}
+
+#[cfg(windows)]
+fn platform_data_directory(variable: &impl Fn(&str) -> Option<OsString>) -> Option<PathBuf> {
+    absolute_path_variable(variable, "LOCALAPPDATA")
+}
+
+#[cfg(target_os = "macos")]
+fn platform_data_directory(variable: &impl Fn(&str) -> Option<OsString>) -> Option<PathBuf> {
+    absolute_path_variable(variable, "HOME")
+        .map(|home| home.join("Library").join("Application Support"))
+}
+
+/// Follows the XDG base directory specification.
+#[cfg(all(unix, not(target_os = "macos")))]
+fn platform_data_directory(variable: &impl Fn(&str) -> Option<OsString>) -> Option<PathBuf> {
+    absolute_path_variable(variable, "XDG_DATA_HOME").or_else(|| {
+        absolute_path_variable(variable, "HOME").map(|home| home.join(".local").join("share"))
+    })
+}
+
+#[cfg(not(any(unix, windows)))]
+fn platform_data_directory(_variable: &impl Fn(&str) -> Option<OsString>) -> Option<PathBuf> {
+    None
+}
+
+/// Relative values are ignored, as the specification asks for XDG variables.
+fn absolute_path_variable(
+    variable: &impl Fn(&str) -> Option<OsString>,
+    name: &str,
+) -> Option<PathBuf> {
+    variable(name)
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+}
+
+#[cfg(all(test, unix, not(target_os = "macos")))]
+mod tests {
+    use super::*;
+
+    fn resolve(vault_dir_override: Option<&str>, variables: &[(&str, &str)]) -> Option<PathBuf> {
+        let variable = |name: &str| {
+            variables
+                .iter()
+                .find(|(variable, _)| *variable == name)
+                .map(|(_, value)| OsString::from(value))
+        };
+
+        resolve_vault_directory(vault_dir_override.map(PathBuf::from), variable).ok()
+    }
+
+    #[test]
+    fn the_vault_directory_follows_precedence() {
+        let everything = [
+            (HOME_VARIABLE, "/opt/rusty"),
+            ("XDG_DATA_HOME", "/data"),
+            ("HOME", "/home/alice"),
+        ];
+
+        assert_eq!(
+            resolve(Some("vaults"), &everything),
+            Some(PathBuf::from("vaults"))
+        );
+        assert_eq!(
+            resolve(None, &everything),
+            Some(PathBuf::from("/opt/rusty"))
+        );
+        assert_eq!(
+            resolve(None, &everything[1..]),
+            Some(PathBuf::from("/data/rusty-lock"))
+        );
+        assert_eq!(
+            resolve(None, &everything[2..]),
+            Some(PathBuf::from("/home/alice/.local/share/rusty-lock"))
+        );
+    }
+
+    #[test]
+    fn empty_and_relative_variables_are_ignored() {
+        assert_eq!(
+            resolve(
+                None,
+                &[
+                    (HOME_VARIABLE, ""),
+                    ("XDG_DATA_HOME", "relative/data"),
+                    ("HOME", "/home/alice")
+                ]
+            ),
+            Some(PathBuf::from("/home/alice/.local/share/rusty-lock"))
+        );
+        assert_eq!(resolve(None, &[("HOME", "home/alice")]), None);
+    }
+}
@@ -0,0 +1,124 @@
+/// Points for every matched character.
+const MATCH: i64 = 16;
+/// Extra points for a character right after the previous match.
+const CONSECUTIVE: i64 = 12;
+/// Extra points for a character starting a word, e.g. after a space, `.` or `-`.
+const WORD_START: i64 = 8;
+/// Points lost for every character skipped between two matches.
+const GAP: i64 = 1;
+
+/// How `query` was found in a text.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FuzzyMatch {
+    /// Higher is better; only comparable between matches of the same query.
+    pub score: i64,
+    /// Character indices of the matched characters, in order.
+    pub positions: Vec<usize>,
+}
+
+/// Finds the characters of `query` in `text` in order, ignoring case, preferring runs of
+/// consecutive characters and the starts of words. An empty query matches anything.
+pub fn fuzzy_match(query: &str, text: &str) -> Option<FuzzyMatch> {
+    let query: Vec<char> = query.chars().flat_map(char::to_lowercase).collect();
+    let original: Vec<char> = text.chars().collect();
+    let text: Vec<char> = original
+        .iter()
+        .map(|c| c.to_lowercase().next().unwrap_or(*c))
+        .collect();
+
+    if query.is_empty() {
+        return Some(FuzzyMatch {
+            score: 0,
+            positions: vec![],
+        });
+    }
+    if query.len() > text.len() {
+        return None;
+    }
+
+    // best[i][j] is the best score of query[..=i] with query[i] on text[j], and the
+    // position of query[i - 1] it was reached from.
+    let mut best: Vec<Vec<Option<(i64, usize)>>> = vec![vec![None; text.len()]; query.len()];
+
+    for (i, wanted) in query.iter().enumerate() {
+        for j in i..text.len() {
+            if text[j] != *wanted {
+                continue;
+            }
+            let mut bonus = MATCH;
+            if is_word_start(&original, j) {
+                bonus += WORD_START;
+            }
+
+            if i == 0 {
+                best[i][j] = Some((bonus - GAP * j.min(3) as i64, 0));
+                continue;
+            }
+
+            best[i][j] = (i - 1..j)
+                .filter_map(|k| best[i - 1][k].map(|(score, _)| (score, k)))
+                .map(|(score, k)| {
+                    let link = if k + 1 == j {
+                        CONSECUTIVE
+                    } else {
+                        -GAP * (j - k - 1) as i64
+                    };
+                    (score + link + bonus, k)
+                })
+                .max_by_key(|(score, k)| (*score, *k));
+        }
+    }
+
+    let last = query.len() - 1;
+    let (mut position, score) = best[last]
+        .iter()
+        .enumerate()
+        .filter_map(|(j, cell)| cell.map(|(score, _)| (j, score)))
+        .max_by_key(|(j, score)| (*score, std::cmp::Reverse(*j)))?;
+
+    let mut positions = vec![position];
+    for i in (1..query.len()).rev() {
+        position = best[i][position].map(|(_, from)| from)?;
+        positions.push(position);
+    }
+    positions.reverse();
+
+    Some(FuzzyMatch { score, positions })
+}
+
+fn is_word_start(text: &[char], index: usize) -> bool {
+    match index.checked_sub(1).map(|previous| text[previous]) {
+        None => true,
+        Some(previous) => {
+            !previous.is_alphanumeric()
+                || (previous.is_lowercase() && text[index].is_uppercase())
+                || (previous.is_alphabetic() && text[index].is_numeric())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn characters_are_found_in_order_ignoring_case() {
+        let found = fuzzy_match("gml", "Google Mail").unwrap();
+        assert_eq!(found.positions, vec![0, 7, 10]);
+
+        assert_eq!(fuzzy_match("lmg", "Google Mail"), None);
+        assert_eq!(fuzzy_match("mailbox", "Mail"), None);
+        assert_eq!(fuzzy_match("", "anything").unwrap().positions, vec![]);
+    }
+
+    #[test]
+    fn runs_and_word_starts_are_preferred() {
+        // The `b` of "bank" rather than the one in "Jobs".
+        let found = fuzzy_match("bank", "Jobs bank").unwrap();
+        assert_eq!(found.positions, vec![5, 6, 7, 8]);
+
+        let score = |text| fuzzy_match("git", text).unwrap().score;
+        assert!(score("GitHub") > score("Digital Ocean"));
+        assert!(score("Digital Ocean") > score("Google Identity Toolkit"));
+    }
+}
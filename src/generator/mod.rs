@@ -0,0 +1,330 @@
+pub mod passphrase;
+
+use std::fmt;
+
+use rand::{seq::SliceRandom, Rng};
+
+use passphrase::{Capitalization, PassphraseOptions, MAX_WORDS};
+
+pub const UPPERCASE: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+pub const LOWERCASE: &str = "abcdefghijklmnopqrstuvwxyz";
+pub const DIGITS: &str = "0123456789";
+pub const SYMBOLS: &str = "!@#$%^&*()-_=+[]{};:,.<>/?~";
+/// Characters easily confused with one another in many fonts.
+pub const AMBIGUOUS: &str = "0OoIl1|`'\"";
+
+pub const MAX_LENGTH: usize = 128;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum GeneratorMode {
+    /// Random characters from the enabled classes.
+    Characters,
+    /// Random words from the embedded list, see [`passphrase`].
+    Passphrase,
+}
+
+/// What a generated password may be made of.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GeneratorOptions {
+    pub mode: GeneratorMode,
+    pub length: usize,
+    pub uppercase: bool,
+    pub lowercase: bool,
+    pub digits: bool,
+    pub symbols: bool,
+    /// Extra characters, used as a class of their own when not empty.
+    pub custom: String,
+    /// How many characters every enabled class contributes at least.
+    pub min_per_class: usize,
+    pub exclude_ambiguous: bool,
+    pub passphrase: PassphraseOptions,
+}
+
+impl Default for GeneratorOptions {
+    fn default() -> Self {
+        GeneratorOptions {
+            mode: GeneratorMode::Characters,
+            length: 20,
+            uppercase: true,
+            lowercase: true,
+            digits: true,
+            symbols: true,
+            custom: String::new(),
+            min_per_class: 1,
+            exclude_ambiguous: false,
+            passphrase: PassphraseOptions::default(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GeneratorError {
+    EmptyLength,
+    TooLong,
+    NoCharacters,
+    /// Every class cannot get its minimum within the length.
+    TooShortForClasses {
+        needed: usize,
+    },
+    NoWords,
+    TooManyWords,
+}
+
+impl fmt::Display for GeneratorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GeneratorError::EmptyLength => write!(f, "the password needs at least one character"),
+            GeneratorError::TooLong => {
+                write!(f, "passwords are limited to {MAX_LENGTH} characters")
+            }
+            GeneratorError::NoCharacters => write!(f, "no character class is enabled"),
+            GeneratorError::TooShortForClasses { needed } => write!(
+                f,
+                "the minimum per class needs a length of at least {needed}"
+            ),
+            GeneratorError::NoWords => write!(f, "the passphrase needs at least one word"),
+            GeneratorError::TooManyWords => {
+                write!(f, "passphrases are limited to {MAX_WORDS} words")
+            }
+        }
+    }
+}
+
+impl GeneratorOptions {
+    /// The enabled classes, without ambiguous characters if they are excluded, skipping
+    /// classes left empty by that.
+    pub fn classes(&self) -> Vec<Vec<char>> {
+        let mut custom: Vec<char> = self.custom.chars().filter(|c| !c.is_control()).collect();
+        custom.sort_unstable();
+        custom.dedup();
+
+        [
+            (self.uppercase, UPPERCASE.chars().collect()),
+            (self.lowercase, LOWERCASE.chars().collect()),
+            (self.digits, DIGITS.chars().collect()),
+            (self.symbols, SYMBOLS.chars().collect()),
+            (true, custom),
+        ]
+        .into_iter()
+        .filter(|(enabled, _)| *enabled)
+        .map(|(_, class): (bool, Vec<char>)| {
+            class
+                .into_iter()
+                .filter(|c| !self.exclude_ambiguous || !AMBIGUOUS.contains(*c))
+                .collect::<Vec<_>>()
+        })
+        .filter(|class| !class.is_empty())
+        .collect()
+    }
+
+    /// Writes the options as `name=value` lines, read back by [`GeneratorOptions::parse`].
+    pub fn serialize(&self) -> String {
+        let flag = |enabled: bool| if enabled { "1" } else { "0" };
+
+        let mode = match self.mode {
+            GeneratorMode::Characters => "characters",
+            GeneratorMode::Passphrase => "passphrase",
+        };
+        let passphrase = &self.passphrase;
+
+        format!(
+            "mode={}\nlength={}\nuppercase={}\nlowercase={}\ndigits={}\nsymbols={}\ncustom={}\n\
+             min_per_class={}\nexclude_ambiguous={}\npassphrase.words={}\n\
+             passphrase.separator={}\npassphrase.capitalization={}\npassphrase.digit={}\n\
+             passphrase.symbol={}\n",
+            mode,
+            self.length,
+            flag(self.uppercase),
+            flag(self.lowercase),
+            flag(self.digits),
+            flag(self.symbols),
+            self.custom.replace('\n', ""),
+            self.min_per_class,
+            flag(self.exclude_ambiguous),
+            passphrase.words,
+            passphrase.separator.replace('\n', ""),
+            passphrase.capitalization.name(),
+            flag(passphrase.digit),
+            flag(passphrase.symbol),
+        )
+    }
+
+    /// Reads options written by [`GeneratorOptions::serialize`], keeping the default of any
+    /// option that is missing or unreadable.
+    pub fn parse(text: &str) -> Self {
+        let mut options = GeneratorOptions::default();
+
+        for (name, value) in text.lines().filter_map(|line| line.split_once('=')) {
+            let flag = value == "1";
+            let passphrase = &mut options.passphrase;
+            match name {
+                "mode" if value == "characters" => options.mode = GeneratorMode::Characters,
+                "mode" if value == "passphrase" => options.mode = GeneratorMode::Passphrase,
+                "length" => options.length = value.parse().unwrap_or(options.length),
+                "uppercase" => options.uppercase = flag,
+                "lowercase" => options.lowercase = flag,
+                "digits" => options.digits = flag,
+                "symbols" => options.symbols = flag,
+                "custom" => options.custom = value.to_string(),
+                "min_per_class" => {
+                    options.min_per_class = value.parse().unwrap_or(options.min_per_class)
+                }
+                "exclude_ambiguous" => options.exclude_ambiguous = flag,
+                "passphrase.words" => passphrase.words = value.parse().unwrap_or(passphrase.words),
+                "passphrase.separator" => passphrase.separator = value.to_string(),
+                "passphrase.capitalization" => {
+                    passphrase.capitalization =
+                        Capitalization::from_name(value).unwrap_or(passphrase.capitalization)
+                }
+                "passphrase.digit" => passphrase.digit = flag,
+                "passphrase.symbol" => passphrase.symbol = flag,
+                _ => {}
+            }
+        }
+
+        options
+    }
+}
+
+/// A random password or passphrase, depending on the mode of `options`.
+pub fn generate(options: &GeneratorOptions, rng: &mut impl Rng) -> Result<String, GeneratorError> {
+    match options.mode {
+        GeneratorMode::Characters => random_characters(options, rng),
+        GeneratorMode::Passphrase => passphrase::generate(&options.passphrase, rng),
+    }
+}
+
+/// Roughly how many bits of entropy a password generated with `options` has. The minimum
+/// per class is ignored, which overestimates a little.
+pub fn entropy_bits(options: &GeneratorOptions) -> f64 {
+    match options.mode {
+        GeneratorMode::Characters => {
+            let mut all: Vec<char> = options.classes().into_iter().flatten().collect();
+            all.sort_unstable();
+            all.dedup();
+
+            options.length as f64 * (all.len().max(1) as f64).log2()
+        }
+        GeneratorMode::Passphrase => passphrase::entropy_bits(&options.passphrase),
+    }
+}
+
+/// A random password with every class present at least `min_per_class` times.
+fn random_characters(
+    options: &GeneratorOptions,
+    rng: &mut impl Rng,
+) -> Result<String, GeneratorError> {
+    if options.length == 0 {
+        return Err(GeneratorError::EmptyLength);
+    }
+    if options.length > MAX_LENGTH {
+        return Err(GeneratorError::TooLong);
+    }
+
+    let classes = options.classes();
+    if classes.is_empty() {
+        return Err(GeneratorError::NoCharacters);
+    }
+
+    let needed = classes.len() * options.min_per_class;
+    if needed > options.length {
+        return Err(GeneratorError::TooShortForClasses { needed });
+    }
+
+    let mut all: Vec<char> = classes.iter().flatten().copied().collect();
+    all.sort_unstable();
+    all.dedup();
+
+    let mut password = Vec::with_capacity(options.length);
+    for class in &classes {
+        for _ in 0..options.min_per_class {
+            password.push(*class.choose(rng).unwrap());
+        }
+    }
+    while password.len() < options.length {
+        password.push(*all.choose(rng).unwrap());
+    }
+    password.shuffle(rng);
+
+    Ok(password.into_iter().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn passwords_honour_length_and_minimum_per_class() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let options = GeneratorOptions {
+            length: 8,
+            custom: String::from("§"),
+            min_per_class: 1,
+            exclude_ambiguous: true,
+            ..Default::default()
+        };
+
+        for _ in 0..1000 {
+            let password = generate(&options, &mut rng).unwrap();
+
+            assert_eq!(password.chars().count(), 8);
+            for class in [UPPERCASE, LOWERCASE, DIGITS, SYMBOLS, "§"] {
+                assert!(password.chars().any(|c| class.contains(c)), "{password}");
+            }
+            assert!(
+                !password.chars().any(|c| AMBIGUOUS.contains(c)),
+                "{password}"
+            );
+        }
+    }
+
+    #[test]
+    fn impossible_options_are_rejected() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let none = GeneratorOptions {
+            uppercase: false,
+            lowercase: false,
+            digits: false,
+            symbols: false,
+            ..Default::default()
+        };
+        let crowded = GeneratorOptions {
+            length: 7,
+            min_per_class: 2,
+            ..Default::default()
+        };
+
+        assert_eq!(generate(&none, &mut rng), Err(GeneratorError::NoCharacters));
+        assert_eq!(
+            generate(&crowded, &mut rng),
+            Err(GeneratorError::TooShortForClasses { needed: 8 })
+        );
+    }
+
+    #[test]
+    fn options_survive_serialization() {
+        let options = GeneratorOptions {
+            length: 16,
+            symbols: false,
+            custom: String::from("=;é"),
+            min_per_class: 3,
+            exclude_ambiguous: true,
+            passphrase: PassphraseOptions {
+                words: 4,
+                separator: String::from(" "),
+                capitalization: Capitalization::Random,
+                digit: true,
+                symbol: false,
+            },
+            ..Default::default()
+        };
+
+        assert_eq!(GeneratorOptions::parse(&options.serialize()), options);
+        assert_eq!(
+            GeneratorOptions::parse("garbage"),
+            GeneratorOptions::default()
+        );
+    }
+}
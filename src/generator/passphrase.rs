@@ -0,0 +1,194 @@
+use rand::{seq::SliceRandom, Rng};
+
+use super::{GeneratorError, DIGITS, SYMBOLS};
+
+/// The BIP-0039 English list: 2048 common words of 3 to 8 letters, none sharing its first
+/// four letters with another, so each word carries 11 bits.
+///
+/// This is not the EFF long list usually meant by diceware: its 7776 words carry about 12.9
+/// bits each, so a passphrase from this list needs about one word in six more to be as strong.
+/// The default length makes up for it, and the entropy shown is always worked out from the
+/// list actually used.
+const WORDLIST: &str = include_str!("wordlist.txt");
+
+pub const MAX_WORDS: usize = 20;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Capitalization {
+    Lowercase,
+    /// Only the first letter of every word.
+    Capitalized,
+    Uppercase,
+    /// A coin flip per word between lowercase and capitalized, one extra bit per word.
+    Random,
+}
+
+impl Capitalization {
+    pub const ALL: [Capitalization; 4] = [
+        Capitalization::Lowercase,
+        Capitalization::Capitalized,
+        Capitalization::Uppercase,
+        Capitalization::Random,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Capitalization::Lowercase => "lowercase",
+            Capitalization::Capitalized => "capitalized",
+            Capitalization::Uppercase => "uppercase",
+            Capitalization::Random => "random",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Capitalization::ALL
+            .into_iter()
+            .find(|capitalization| capitalization.name() == name)
+    }
+
+    fn apply(self, word: &str, rng: &mut impl Rng) -> String {
+        let capitalize = |word: &str| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect())
+                .unwrap_or_default()
+        };
+
+        match self {
+            Capitalization::Lowercase => word.to_string(),
+            Capitalization::Capitalized => capitalize(word),
+            Capitalization::Uppercase => word.to_uppercase(),
+            Capitalization::Random if rng.gen_bool(0.5) => capitalize(word),
+            Capitalization::Random => word.to_string(),
+        }
+    }
+}
+
+/// How a passphrase of random words is put together.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PassphraseOptions {
+    pub words: usize,
+    pub separator: String,
+    pub capitalization: Capitalization,
+    /// Appends a random digit to one of the words.
+    pub digit: bool,
+    /// Appends a random symbol to one of the words.
+    pub symbol: bool,
+}
+
+impl Default for PassphraseOptions {
+    fn default() -> Self {
+        PassphraseOptions {
+            // As strong as six words from the EFF long list, about 77 bits.
+            words: 7,
+            separator: String::from("-"),
+            capitalization: Capitalization::Lowercase,
+            digit: false,
+            symbol: false,
+        }
+    }
+}
+
+pub fn wordlist() -> impl Iterator<Item = &'static str> {
+    WORDLIST.lines()
+}
+
+/// A passphrase of `options.words` words drawn uniformly from the embedded list.
+pub fn generate(options: &PassphraseOptions, rng: &mut impl Rng) -> Result<String, GeneratorError> {
+    if options.words == 0 {
+        return Err(GeneratorError::NoWords);
+    }
+    if options.words > MAX_WORDS {
+        return Err(GeneratorError::TooManyWords);
+    }
+
+    let wordlist: Vec<&str> = wordlist().collect();
+    let mut words: Vec<String> = (0..options.words)
+        .map(|_| {
+            options
+                .capitalization
+                .apply(wordlist.choose(rng).unwrap(), rng)
+        })
+        .collect();
+
+    for (enabled, extras) in [(options.digit, DIGITS), (options.symbol, SYMBOLS)] {
+        if enabled {
+            let extras: Vec<char> = extras.chars().collect();
+            let word = rng.gen_range(0..words.len());
+            words[word].push(*extras.choose(rng).unwrap());
+        }
+    }
+
+    Ok(words.join(&options.separator))
+}
+
+/// Bits of entropy of a passphrase generated with `options`, assuming the attacker knows
+/// the list and the options but not the random choices.
+pub fn entropy_bits(options: &PassphraseOptions) -> f64 {
+    let words = options.words as f64;
+    let mut bits = words * (wordlist().count() as f64).log2();
+
+    if options.capitalization == Capitalization::Random {
+        bits += words;
+    }
+    for (enabled, extras) in [(options.digit, DIGITS), (options.symbol, SYMBOLS)] {
+        if enabled {
+            bits += (extras.chars().count() as f64 * words).log2();
+        }
+    }
+
+    bits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand::{rngs::StdRng, SeedableRng};
+    use std::collections::HashSet;
+
+    #[test]
+    fn wordlist_is_unique_and_unambiguous() {
+        let words: Vec<&str> = wordlist().collect();
+        let prefixes: HashSet<String> = words.iter().map(|w| w.chars().take(4).collect()).collect();
+
+        assert_eq!(words.len(), 2048);
+        assert_eq!(prefixes.len(), words.len());
+        assert!(words
+            .iter()
+            .all(|w| w.chars().all(|c| c.is_ascii_lowercase())));
+    }
+
+    #[test]
+    fn passphrases_follow_the_options() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let options = PassphraseOptions {
+            words: 5,
+            separator: String::from(" "),
+            capitalization: Capitalization::Capitalized,
+            digit: true,
+            symbol: true,
+        };
+
+        for _ in 0..200 {
+            let passphrase = generate(&options, &mut rng).unwrap();
+            let words: Vec<&str> = passphrase.split(' ').collect();
+
+            assert_eq!(words.len(), 5, "{passphrase}");
+            assert!(words.iter().all(|w| w.starts_with(char::is_uppercase)));
+            assert!(passphrase.contains(|c| DIGITS.contains(c)), "{passphrase}");
+            assert!(passphrase.contains(|c| SYMBOLS.contains(c)), "{passphrase}");
+        }
+    }
+
+    #[test]
+    fn entropy_counts_every_random_choice() {
+        let mut options = PassphraseOptions::default();
+        assert_eq!(entropy_bits(&options), 77.0);
+
+        options.capitalization = Capitalization::Random;
+        options.digit = true;
+        assert_eq!(entropy_bits(&options), 84.0 + 70f64.log2());
+    }
+}
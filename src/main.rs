@@ -1,16 +1,166 @@
 pub mod app;
+pub mod backoff;
+pub mod clipboard;
 pub mod components;
 pub mod crypto_utils;
+pub mod data_dir;
+pub mod fuzzy;
+pub mod generator;
 pub mod message_bus;
 pub mod screens;
+pub mod settings;
+pub mod strength;
+pub mod totp;
+pub mod vault;
 
 use app::App;
-use std::io;
+use clipboard::Clipboard;
+use settings::Settings;
+use std::{
+    cell::RefCell,
+    env,
+    ffi::OsString,
+    io::{self, ErrorKind},
+    path::PathBuf,
+    rc::Rc,
+    time::Duration,
+};
+use vault::{file_store::FileStore, persistence};
+
+const USAGE: &str = "usage: rusty-lock [--vault-dir <path>] [--clipboard-timeout <seconds>] \
+                     [--history-depth <count>] [--reveal-timeout <seconds>] \
+                     [--lock-timeout <seconds>] [--backups <count>]";
+
+struct Arguments {
+    vault_dir: Option<PathBuf>,
+    /// `None` keeps copied secrets on the clipboard, set with `--clipboard-timeout 0`.
+    clipboard_timeout: Option<Duration>,
+    /// Previous versions kept of every vault, set with `--backups`.
+    backup_count: usize,
+    settings: Settings,
+}
 
 fn main() -> io::Result<()> {
+    let arguments = parse_arguments()?;
+    let vault_directory = data_dir::vault_directory(arguments.vault_dir)?;
+    // A directory given by the user may be shared with other files, so it is left alone.
+    if data_dir::is_application_directory(&vault_directory) {
+        persistence::tighten_directory(&vault_directory)?;
+    }
+    let legacy_directory = data_dir::legacy_vault_directory(&vault_directory);
+    let clipboard = Clipboard::new(clipboard::detect(), arguments.clipboard_timeout);
+
     let mut terminal = ratatui::init();
-    let mut app = App::new();
+    let mut app = App::new(
+        Rc::new(RefCell::new(FileStore::new(
+            vault_directory.clone(),
+            arguments.backup_count,
+        ))),
+        clipboard,
+        arguments.settings,
+    );
     let app_result = app.run(&mut terminal);
     ratatui::restore();
+
+    // Printed once the terminal is restored, so it stays readable.
+    if let Some(legacy_directory) = legacy_directory {
+        eprintln!(
+            "note: found vaults of an earlier version in {}, which is no longer read. Move them \
+             to {} or pass --vault-dir {} to keep using them.",
+            legacy_directory.display(),
+            vault_directory.display(),
+            legacy_directory.display(),
+        );
+    }
+
     app_result
 }
+
+/// Reads `--vault-dir <path>`, `--clipboard-timeout <seconds>`, `--backups <count>`,
+/// `--history-depth <count>`, `--reveal-timeout <seconds>` and `--lock-timeout <seconds>`,
+/// also accepted as `--flag=value`.
+fn parse_arguments() -> io::Result<Arguments> {
+    let mut arguments = Arguments {
+        vault_dir: None,
+        clipboard_timeout: Some(clipboard::DEFAULT_CLEAR_AFTER),
+        backup_count: persistence::DEFAULT_BACKUP_COUNT,
+        settings: Settings::default(),
+    };
+    let mut args = env::args_os().skip(1);
+
+    while let Some(arg) = args.next() {
+        let (flag, inline_value) = match arg.to_str().and_then(|arg| arg.split_once('=')) {
+            Some((flag, value)) => (OsString::from(flag), Some(OsString::from(value))),
+            None => (arg, None),
+        };
+
+        let value = || {
+            inline_value
+                .or_else(|| args.next())
+                .ok_or_else(|| invalid_argument(format!("{flag:?} requires a value, {USAGE}")))
+        };
+
+        match flag.to_str() {
+            Some("--vault-dir") => arguments.vault_dir = Some(PathBuf::from(value()?)),
+            Some("--clipboard-timeout") => {
+                let seconds: u64 = value()?
+                    .to_str()
+                    .and_then(|seconds| seconds.parse().ok())
+                    .ok_or_else(|| {
+                        invalid_argument(format!("--clipboard-timeout expects seconds, {USAGE}"))
+                    })?;
+
+                arguments.clipboard_timeout = (seconds > 0).then(|| Duration::from_secs(seconds));
+            }
+            Some("--backups") => {
+                arguments.backup_count = value()?
+                    .to_str()
+                    .and_then(|count| count.parse().ok())
+                    .ok_or_else(|| {
+                        invalid_argument(format!("--backups expects a count, {USAGE}"))
+                    })?;
+            }
+            Some("--history-depth") => {
+                arguments.settings.history_depth = value()?
+                    .to_str()
+                    .and_then(|count| count.parse().ok())
+                    .ok_or_else(|| {
+                        invalid_argument(format!("--history-depth expects a count, {USAGE}"))
+                    })?;
+            }
+            Some("--reveal-timeout") => {
+                let seconds: u64 = value()?
+                    .to_str()
+                    .and_then(|seconds| seconds.parse().ok())
+                    .ok_or_else(|| {
+                        invalid_argument(format!("--reveal-timeout expects seconds, {USAGE}"))
+                    })?;
+
+                arguments.settings.reveal_timeout =
+                    (seconds > 0).then(|| Duration::from_secs(seconds));
+            }
+            Some("--lock-timeout") => {
+                let seconds: u64 = value()?
+                    .to_str()
+                    .and_then(|seconds| seconds.parse().ok())
+                    .ok_or_else(|| {
+                        invalid_argument(format!("--lock-timeout expects seconds, {USAGE}"))
+                    })?;
+
+                arguments.settings.lock_timeout =
+                    (seconds > 0).then(|| Duration::from_secs(seconds));
+            }
+            _ => {
+                return Err(invalid_argument(format!(
+                    "unknown argument {flag:?}, {USAGE}"
+                )))
+            }
+        }
+    }
+
+    Ok(arguments)
+}
+
+fn invalid_argument(message: String) -> io::Error {
+    io::Error::new(ErrorKind::InvalidInput, message)
+}
@@ -1,22 +1,90 @@
-#[derive(Clone)]
+use std::time::{Duration, Instant};
+
+use crate::vault::Vault;
+
 pub enum Message {
-    LoginCredentials(String, String),
+    Unlocked(Vault),
+    /// The vault of the account with this login was locked and has to be unlocked again.
+    Locked(String),
+    /// The timeout of a revealed password may have passed.
+    RevealExpired,
+}
+
+/// A message to deliver once `deadline` has passed.
+struct Timer {
+    deadline: Instant,
+    message: Message,
 }
 
 pub struct MessageBus {
     messages: Vec<Message>,
+    timers: Vec<Timer>,
+}
+
+impl Default for MessageBus {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl MessageBus {
     pub fn new() -> Self {
-        MessageBus { messages: vec![] }
+        MessageBus {
+            messages: vec![],
+            timers: vec![],
+        }
     }
 
     pub fn submit_message(&mut self, message: Message) {
         self.messages.push(message);
     }
 
+    /// Submits `message` once `delay` has passed, on the first poll after that.
+    pub fn schedule(&mut self, delay: Duration, message: Message) {
+        self.timers.push(Timer {
+            deadline: Instant::now() + delay,
+            message,
+        });
+    }
+
+    /// When the next scheduled message is due, if any.
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.timers.iter().map(|timer| timer.deadline).min()
+    }
+
+    /// The submitted messages, followed by the scheduled ones that are due, earliest first.
     pub fn poll_messages(&mut self) -> Vec<Message> {
-        self.messages.drain(..).collect()
+        let now = Instant::now();
+        let (mut due, pending): (Vec<Timer>, Vec<Timer>) = self
+            .timers
+            .drain(..)
+            .partition(|timer| timer.deadline <= now);
+        self.timers = pending;
+        due.sort_by_key(|timer| timer.deadline);
+
+        self.messages
+            .drain(..)
+            .chain(due.into_iter().map(|timer| timer.message))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scheduled_messages_wait_for_their_deadline() {
+        let mut bus = MessageBus::new();
+        bus.schedule(Duration::from_secs(3600), Message::RevealExpired);
+        assert!(bus.poll_messages().is_empty());
+
+        bus.schedule(Duration::ZERO, Message::RevealExpired);
+        assert!(bus.next_deadline().is_some_and(|due| due <= Instant::now()));
+
+        let messages = bus.poll_messages();
+        assert!(matches!(messages.as_slice(), [Message::RevealExpired]));
+        assert!(bus.poll_messages().is_empty());
+        assert!(bus.next_deadline().is_some());
     }
 }
@@ -0,0 +1,268 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::{
+    prelude::*,
+    widgets::{block::Title, Block, Paragraph},
+};
+use secrecy::{zeroize::Zeroizing, ExposeSecret, SecretString};
+use symbols::border;
+
+use crate::{
+    app::{AppState, Screen},
+    components::{
+        input_field::{InputField, InputFieldState, MASTER_PASSWORD_LIMIT},
+        strength_meter::{StrengthMeter, METER_HEIGHT},
+    },
+    message_bus::{Message, MessageBus},
+    strength,
+    vault::{self, store::VaultStore},
+};
+
+const MIN_PASSWORD_LENGTH: usize = 8;
+
+pub struct CreateAccountScreen {
+    login_input: InputField,
+    password_input: InputField,
+    confirm_input: InputField,
+    active_field: ActiveField,
+    message_bus: Rc<RefCell<MessageBus>>,
+    vault_store: Rc<RefCell<dyn VaultStore>>,
+
+    error_message: Option<String>,
+}
+
+#[derive(Copy, Clone, PartialEq)]
+enum ActiveField {
+    Login,
+    Password,
+    Confirm,
+}
+
+impl CreateAccountScreen {
+    pub fn new(
+        message_bus: Rc<RefCell<MessageBus>>,
+        vault_store: Rc<RefCell<dyn VaultStore>>,
+    ) -> Self {
+        let mut login_input = InputField::default();
+        login_input.label = "Login";
+        login_input.state = InputFieldState::Active;
+
+        let mut password_input = InputField::default();
+        password_input.label = "Master password";
+        password_input.hide_value = true;
+        password_input.character_limit = MASTER_PASSWORD_LIMIT;
+
+        let mut confirm_input = InputField::default();
+        confirm_input.label = "Confirm master password";
+        confirm_input.hide_value = true;
+        confirm_input.character_limit = MASTER_PASSWORD_LIMIT;
+
+        CreateAccountScreen {
+            login_input,
+            password_input,
+            confirm_input,
+            active_field: ActiveField::Login,
+            message_bus,
+            vault_store,
+            error_message: None,
+        }
+    }
+
+    fn handle_submit(&mut self, state: &mut AppState) {
+        match self.active_field {
+            ActiveField::Login => self.focus(ActiveField::Password),
+            ActiveField::Password => self.focus(ActiveField::Confirm),
+            ActiveField::Confirm => {
+                let password = SecretString::from(self.password_input.get_value());
+
+                if password.expose_secret().chars().count() < MIN_PASSWORD_LENGTH {
+                    self.error_message = Some(format!(
+                        "The master password must be at least {MIN_PASSWORD_LENGTH} characters long"
+                    ));
+                    return;
+                }
+
+                let confirmation = Zeroizing::new(self.confirm_input.get_value());
+                if password.expose_secret() != confirmation.as_str() {
+                    self.error_message = Some(String::from("The passwords do not match"));
+                    self.confirm_input.clear_value();
+                    return;
+                }
+
+                let login = self.login_input.get_value();
+                let created = vault::create(&mut *self.vault_store.borrow_mut(), &login, password);
+                match created {
+                    Ok(vault) => {
+                        self.message_bus
+                            .borrow_mut()
+                            .submit_message(Message::Unlocked(vault));
+
+                        self.reset();
+                        *state = AppState::Dashboard;
+                    }
+                    Err(why) => {
+                        self.error_message = Some(format!("Couldn't create account: {why}"))
+                    }
+                }
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        self.login_input.clear_value();
+        self.password_input.clear_value();
+        self.confirm_input.clear_value();
+        self.error_message = None;
+        self.focus(ActiveField::Login);
+    }
+
+    fn focus(&mut self, field: ActiveField) {
+        self.active_field = field;
+
+        for (input, input_field) in [
+            (&mut self.login_input, ActiveField::Login),
+            (&mut self.password_input, ActiveField::Password),
+            (&mut self.confirm_input, ActiveField::Confirm),
+        ] {
+            input.state = if input_field == field {
+                InputFieldState::Active
+            } else {
+                InputFieldState::Inactive
+            };
+        }
+    }
+
+    fn focus_previous(&mut self) {
+        match self.active_field {
+            ActiveField::Login => {}
+            ActiveField::Password => self.focus(ActiveField::Login),
+            ActiveField::Confirm => self.focus(ActiveField::Password),
+        }
+    }
+
+    fn focus_next(&mut self) {
+        match self.active_field {
+            ActiveField::Login => self.focus(ActiveField::Password),
+            ActiveField::Password => self.focus(ActiveField::Confirm),
+            ActiveField::Confirm => {}
+        }
+    }
+
+    fn handle_input_field_event(&mut self, key_code: KeyCode) {
+        let active_input = match self.active_field {
+            ActiveField::Login => &mut self.login_input,
+            ActiveField::Password => &mut self.password_input,
+            ActiveField::Confirm => &mut self.confirm_input,
+        };
+
+        match key_code {
+            KeyCode::Backspace => active_input.remove_character(),
+            KeyCode::Left => active_input.move_cursor_left(),
+            KeyCode::Right => active_input.move_cursor_right(),
+            KeyCode::Home => active_input.reset_cursor(),
+            KeyCode::End => active_input.place_cursor_at_end(),
+
+            KeyCode::Char(c) => active_input.add_character(c),
+            _ => {}
+        }
+    }
+}
+
+impl Screen for CreateAccountScreen {
+    fn render(&mut self, frame: &mut Frame) {
+        let area = frame.area();
+        let buf = frame.buffer_mut();
+
+        let title = Title::from(" Create an account ".bold());
+        let block = Block::bordered()
+            .title(title.alignment(Alignment::Center))
+            .border_set(border::THICK);
+
+        let text = Text::from(Line::from(vec![
+            "Choose a login and a master password, or press ".into(),
+            "<Esc> ".bold(),
+            "to go back to logging in".into(),
+        ]));
+
+        let layout_parts = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(3),
+                Constraint::Min(1),
+                Constraint::Length(3),
+            ])
+            .split(area);
+
+        Paragraph::new(text)
+            .block(block)
+            .alignment(Alignment::Center)
+            .render(layout_parts[0], buf);
+
+        Block::bordered()
+            .border_set(border::THICK)
+            .render(layout_parts[1], buf);
+
+        let input_area = Layout::default()
+            .direction(Direction::Vertical)
+            .flex(layout::Flex::Center)
+            .constraints([
+                Constraint::Length(5),
+                Constraint::Length(5),
+                Constraint::Length(5),
+                Constraint::Length(METER_HEIGHT),
+            ])
+            .split(layout_parts[1]);
+
+        let centered =
+            |area: Rect| Rect::new(area.x + area.width / 3, area.y, area.width / 3, area.height);
+
+        self.login_input.render(centered(input_area[0]), buf);
+        self.password_input.render(centered(input_area[1]), buf);
+        self.confirm_input.render(centered(input_area[2]), buf);
+
+        let password = Zeroizing::new(self.password_input.get_value());
+        if !password.is_empty() {
+            StrengthMeter {
+                estimate: &strength::estimate(&password),
+            }
+            .render(input_area[3], buf);
+        }
+
+        if let Some(error_message) = &self.error_message {
+            Paragraph::new(error_message.as_str().red())
+                .alignment(Alignment::Center)
+                .render(layout_parts[2], buf);
+        }
+
+        for input in [&self.login_input, &self.password_input, &self.confirm_input].iter() {
+            if let Some(position) = input.cursor_position {
+                frame.set_cursor_position(position);
+            }
+        }
+    }
+
+    fn handle_terminal_events(&mut self, event: event::Event, state: &mut AppState) {
+        match event {
+            Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
+                self.handle_input_field_event(key_event.code);
+
+                match key_event.code {
+                    KeyCode::PageUp => self.focus_previous(),
+                    KeyCode::PageDown => self.focus_next(),
+                    KeyCode::Enter => self.handle_submit(state),
+                    KeyCode::Esc => {
+                        self.reset();
+                        *state = AppState::WelcomeScreen;
+                    }
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_messages(&mut self, _messages: Vec<Message>, _state: &mut AppState) {}
+
+    fn handle_tick(&mut self, _state: &mut AppState) {}
+}
@@ -1,319 +1,1038 @@
 use std::{
     cell::RefCell,
-    fs::{self, File, OpenOptions},
-    io::{self, Read, Write},
-    path::Path,
+    fs,
     rc::Rc,
+    time::{Duration, Instant},
 };
 
 use crate::{
     app::{AppState, Screen},
+    clipboard::Clipboard,
     components::{
-        input_field::{InputField, InputFieldState},
+        confirm_dialog::ConfirmDialog,
+        entry_form::{EntryForm, FormField},
+        generator_dialog::GeneratorDialog,
         password_list::{PasswordList, PasswordListItem},
     },
-    crypto_utils::{self},
+    crypto_utils::{self, CryptoError},
+    generator::GeneratorOptions,
     message_bus::{Message, MessageBus},
+    settings::Settings,
+    strength::{self, MAX_SCORE},
+    totp::{self, otpauth},
+    vault::{
+        entry::{self, CustomField, Entry, OneTimePassword},
+        store::VaultStore,
+        Entries, Vault,
+    },
 };
 use base64::{prelude::BASE64_STANDARD, Engine};
-use crossterm::event::{Event, KeyCode, KeyEventKind};
-use rand::distributions::{Alphanumeric, DistString};
+use chrono::{DateTime, Local, Utc};
+use crossterm::event::{Event, KeyCode, KeyEventKind, KeyModifiers};
 use ratatui::{
     prelude::*,
     widgets::{
-        block::Title, Block, Borders, HighlightSpacing, List, ListItem, ListState, Paragraph,
+        block::Title, Block, Borders, Clear, HighlightSpacing, List, ListItem, ListState,
+        Paragraph, Wrap,
     },
 };
-use secrecy::{ExposeSecret, SecretBox};
-use symbols::border;
-use windows::Win32::{
-    Foundation::{GlobalFree, HANDLE},
-    System::{
-        DataExchange::{CloseClipboard, EmptyClipboard, OpenClipboard, SetClipboardData},
-        Memory::{GlobalAlloc, GlobalLock, GlobalUnlock, GMEM_MOVEABLE},
-    },
+use secrecy::{
+    zeroize::{Zeroize, Zeroizing},
+    ExposeSecret, SecretString,
 };
+use symbols::border;
 
-#[cfg(not(debug_assertions))]
-const PASSWORD_PATH: &'static str = env!("LOCALAPPDATA");
-
-#[cfg(debug_assertions)]
-const PASSWORD_PATH: &'static str = ".";
+const ENTRY_FIELDS: &[FormField] = &[
+    FormField::Service,
+    FormField::Username,
+    FormField::Password,
+    FormField::Urls,
+    FormField::Tags,
+    FormField::Totp,
+    FormField::Notes,
+    FormField::CustomFields,
+];
 
 #[derive(Copy, Clone)]
 enum DisplayInputs {
     GeneratePassword,
     ImportPassword,
+    /// Reading one-time passwords from an `otpauth://` URI or a file of them.
+    ImportOtp,
+    /// Editing the entry at this index of the password list.
+    EditSelected(usize),
 }
 
+/// A destructive action waiting for the user to confirm it.
 #[derive(Copy, Clone)]
-enum CurrentlyActiveInput {
-    Service,
-    Password,
+enum Confirmation {
+    /// Moving the entry at this index of the password list to the trash.
+    Delete(usize),
+    /// Dropping the entry at this index of the trash for good.
+    Purge(usize),
+}
+
+/// What was selected when the vault was locked, to pick up there after unlocking.
+struct LockedSelection {
+    password: Option<usize>,
+    trash: Option<usize>,
+    show_trash: bool,
+}
+
+/// A password decrypted for the details pane.
+struct RevealedPassword {
+    /// The sealed password it came from, so it is only shown next to that entry.
+    sealed: String,
+    password: SecretString,
+    /// When it gets masked again, `None` to keep it until it is masked by hand.
+    deadline: Option<Instant>,
 }
 
 pub struct Dashboard {
-    service_input: InputField,
-    password_input: InputField,
+    form: EntryForm,
+    generator: GeneratorDialog,
+    /// The options the user generated their last password with, remembered across sessions.
+    generator_options: GeneratorOptions,
     display_inputs: Option<DisplayInputs>,
-    active_input: Option<CurrentlyActiveInput>,
+    /// Whether the details of the selected entry are shown next to the list.
+    show_details: bool,
+    revealed: Option<RevealedPassword>,
+    /// Selection in the password history of the selected entry, while it is shown.
+    history: Option<ListState>,
 
     password_list: PasswordList,
-    user_password: SecretBox<Vec<u8>>,
+    trash_list: PasswordList,
+    /// Whether the search query of the password list is being typed.
+    searching: bool,
+    show_trash: bool,
+    pending_confirmation: Option<Confirmation>,
+    /// Where the last deleted entry was, so it can be put back until the session ends.
+    last_deletion: Option<(usize, Entry)>,
+    vault: Option<Vault>,
+    vault_store: Rc<RefCell<dyn VaultStore>>,
+    message_bus: Rc<RefCell<MessageBus>>,
+    clipboard: Clipboard,
+    settings: Settings,
+    error_banner: Option<String>,
+    /// Confirms that something worked, in place of the error banner until the next key press.
+    notice: Option<String>,
+    /// The last input, after which the vault locks itself once `settings.lock_timeout` passed.
+    last_activity: Instant,
+    locked_selection: Option<LockedSelection>,
 }
 
 impl Dashboard {
-    pub fn new(_message_bus: Rc<RefCell<MessageBus>>) -> Self {
-        let mut service_input = InputField::default();
-        service_input.label = "Service";
-        service_input.state = InputFieldState::Active;
-
-        let mut password_input = InputField::default();
-        password_input.label = "Password";
-        password_input.hide_value = true;
-
-        let dashboard = Dashboard {
-            user_password: SecretBox::new(Box::new(vec![])),
-            service_input,
-            password_input,
+    pub fn new(
+        message_bus: Rc<RefCell<MessageBus>>,
+        vault_store: Rc<RefCell<dyn VaultStore>>,
+        clipboard: Clipboard,
+        settings: Settings,
+    ) -> Self {
+        Dashboard {
+            message_bus,
+            vault: None,
+            vault_store,
+            clipboard,
+            settings,
+            form: EntryForm::new(ENTRY_FIELDS),
+            generator: GeneratorDialog::new(GeneratorOptions::default()),
+            generator_options: GeneratorOptions::default(),
             display_inputs: None,
-            active_input: None,
-            password_list: PasswordList {
-                items: vec![],
-                state: ListState::default(),
-            },
-        };
+            show_details: true,
+            revealed: None,
+            history: None,
+            password_list: PasswordList::from(Vec::new()),
+            trash_list: PasswordList::from(Vec::new()),
+            searching: false,
+            show_trash: false,
+            pending_confirmation: None,
+            last_deletion: None,
+            error_banner: None,
+            notice: None,
+            last_activity: Instant::now(),
+            locked_selection: None,
+        }
+    }
 
-        dashboard
+    /// The master key of the open vault, empty until someone logs in.
+    fn user_password(&self) -> &[u8] {
+        self.vault.as_ref().map_or(&[], Vault::key)
     }
 
-    fn decode_password(&self, encoded_password: &str) -> String {
-        let decoded = crypto_utils::decrypt(
-            BASE64_STANDARD.decode(encoded_password).unwrap().as_slice(),
-            self.user_password.expose_secret(),
-        );
+    fn decode_password(&self, encoded_password: &str) -> Result<String, CryptoError> {
+        let encrypted = BASE64_STANDARD
+            .decode(encoded_password)
+            .map_err(|_| CryptoError::Encoding)?;
+        let decoded = crypto_utils::decrypt(encrypted.as_slice(), self.user_password())?;
 
-        String::from_utf8(decoded).unwrap()
+        String::from_utf8(decoded).map_err(|_| CryptoError::Encoding)
     }
 
-    fn select_next(&mut self) {
-        self.password_list.state.select_next();
+    fn encode_password(&self, password: &str) -> Result<String, CryptoError> {
+        let encrypted = crypto_utils::encrypt(password, self.user_password())?;
+
+        Ok(BASE64_STANDARD.encode(encrypted))
     }
-    fn select_previous(&mut self) {
-        self.password_list.state.select_previous();
+
+    fn copy_selected(&mut self) {
+        let Some(password_index) = self.password_list.selected() else {
+            return;
+        };
+        let entry = &self.password_list.items[password_index].entry;
+        if !entry.has_password() {
+            self.error_banner = Some(format!("\"{}\" has no password", entry.label));
+            return;
+        }
+
+        match self.decode_password(entry.password.as_str()) {
+            Ok(decoded_password) => {
+                if let Err(why) = self.clipboard.copy_secret(decoded_password.as_str()) {
+                    self.error_banner = Some(format!(
+                        "Couldn't copy with {}: {}",
+                        self.clipboard.backend_name(),
+                        why
+                    ));
+                    return;
+                }
+
+                // Saved with the next change; saving now would rotate a backup out per copy.
+                self.password_list.items[password_index].entry.last_used = Some(entry::now());
+            }
+            Err(why) => {
+                self.error_banner = Some(format!("Couldn't decrypt \"{}\": {}", entry.label, why));
+                self.password_list.items[password_index].is_corrupted = true;
+            }
+        }
     }
 
-    fn focus_service(&mut self) {
-        self.active_input = Some(CurrentlyActiveInput::Service);
-        self.service_input.state = InputFieldState::Active;
-        self.password_input.state = InputFieldState::Inactive;
+    /// Decrypts the password of the selected entry into the details pane, or masks it again.
+    fn toggle_reveal(&mut self) {
+        if self.revealed.take().is_some() {
+            return;
+        }
+        let Some(password_index) = self.password_list.selected() else {
+            return;
+        };
+        let entry = &self.password_list.items[password_index].entry;
+        if !entry.has_password() {
+            self.error_banner = Some(format!("\"{}\" has no password", entry.label));
+            return;
+        }
+
+        match self.decode_password(&entry.password) {
+            Ok(password) => {
+                let timeout = self.settings.reveal_timeout;
+                self.revealed = Some(RevealedPassword {
+                    sealed: entry.password.clone(),
+                    password: SecretString::from(password),
+                    deadline: timeout.map(|timeout| Instant::now() + timeout),
+                });
+                if let Some(timeout) = timeout {
+                    self.message_bus
+                        .borrow_mut()
+                        .schedule(timeout, Message::RevealExpired);
+                }
+            }
+            Err(why) => {
+                self.error_banner = Some(format!("Couldn't decrypt \"{}\": {}", entry.label, why));
+                self.password_list.items[password_index].is_corrupted = true;
+            }
+        }
     }
 
-    fn focus_password(&mut self) {
-        self.active_input = Some(CurrentlyActiveInput::Password);
-        self.service_input.state = InputFieldState::Inactive;
-        self.password_input.state = InputFieldState::Active;
+    /// Masks the revealed password once it has been shown for long enough.
+    fn mask_if_expired(&mut self) {
+        let expired = self
+            .revealed
+            .as_ref()
+            .and_then(|revealed| revealed.deadline)
+            .is_some_and(|deadline| Instant::now() >= deadline);
+
+        if expired {
+            self.revealed = None;
+        }
     }
 
-    fn handle_input_field_event(&mut self, key_code: KeyCode) {
-        let active_input = match self.active_input {
-            Some(input) => match input {
-                CurrentlyActiveInput::Service => &mut self.service_input,
-                CurrentlyActiveInput::Password => &mut self.password_input,
-            },
-            None => return,
+    fn copy_from_history(&mut self) {
+        let Some(password_index) = self.password_list.selected() else {
+            return;
+        };
+        let entry = &self.password_list.items[password_index].entry;
+        let Some(previous) = self
+            .history
+            .as_ref()
+            .and_then(ListState::selected)
+            .and_then(|index| entry.history.get(index))
+        else {
+            return;
         };
 
-        match key_code {
-            KeyCode::Esc => {
-                self.service_input.clear_value();
-                self.password_input.clear_value();
-
-                self.display_inputs = None;
-                self.active_input = None;
-            }
-            KeyCode::Backspace => active_input.remove_character(),
-            KeyCode::Left => active_input.move_cursor_left(),
-            KeyCode::Right => active_input.move_cursor_right(),
-            KeyCode::Home => active_input.reset_cursor(),
-            KeyCode::End => active_input.place_cursor_at_end(),
-            KeyCode::Char(c) => active_input.add_character(c),
-            _ => {}
-        }
-    }
-
-    fn copy_to_clipboard(&self, text: &str) {
-        unsafe {
-            // Open the clipboard
-            if OpenClipboard(None).is_ok() {
-                // Empty the clipboard
-                EmptyClipboard();
-
-                // Allocate global memory
-                let h_glob = GlobalAlloc(GMEM_MOVEABLE, text.len() + 1).unwrap();
-                let p_glob = GlobalLock(h_glob);
-                if !p_glob.is_null() {
-                    // Copy the text to the allocated memory
-                    std::ptr::copy_nonoverlapping(text.as_ptr(), p_glob as *mut u8, text.len());
-                    // Add null terminator
-                    *((p_glob as *mut u8).add(text.len())) = 0;
-
-                    GlobalUnlock(h_glob);
-
-                    // Set the clipboard data
-                    SetClipboardData(1, HANDLE(h_glob.0));
-                }
-                GlobalFree(h_glob);
-                CloseClipboard();
+        let copied = self
+            .decode_password(&previous.password)
+            .map_err(|why| format!("Couldn't decrypt the old password: {why}"))
+            .and_then(|decoded_password| {
+                self.clipboard
+                    .copy_secret(&decoded_password)
+                    .map_err(|why| {
+                        format!(
+                            "Couldn't copy with {}: {}",
+                            self.clipboard.backend_name(),
+                            why
+                        )
+                    })
+            });
+
+        if let Err(why) = copied {
+            self.error_banner = Some(why);
+        }
+    }
+
+    fn show_history(&mut self) {
+        let Some(password_index) = self.password_list.selected() else {
+            return;
+        };
+
+        let mut state = ListState::default();
+        if !self.password_list.items[password_index]
+            .entry
+            .history
+            .is_empty()
+        {
+            state.select(Some(0));
+        }
+        self.history = Some(state);
+    }
+
+    fn open_form(&mut self, display_inputs: DisplayInputs) {
+        match display_inputs {
+            DisplayInputs::GeneratePassword => {
+                self.generator = GeneratorDialog::new(self.generator_options.clone())
+            }
+            DisplayInputs::ImportPassword | DisplayInputs::EditSelected(_) => {
+                self.form = EntryForm::new(ENTRY_FIELDS)
             }
+            DisplayInputs::ImportOtp => self.form = EntryForm::new(&[FormField::OtpSource]),
         }
+        self.display_inputs = Some(display_inputs);
     }
 
-    fn load_passwords_from_file(&mut self, login: String) {
-        #[cfg(not(debug_assertions))]
-        let directory_base_path = Path::new(PASSWORD_PATH).join("rusty-lock");
+    fn close_form(&mut self) {
+        self.form.clear();
+        self.generator = GeneratorDialog::new(self.generator_options.clone());
+        self.display_inputs = None;
+    }
 
-        #[cfg(debug_assertions)]
-        let directory_base_path = Path::new(PASSWORD_PATH);
+    fn edit_selected(&mut self) {
+        let Some(password_index) = self.password_list.selected() else {
+            return;
+        };
+        let entry = self.password_list.items[password_index].entry.clone();
 
-        let directory_path = directory_base_path.join("pwds");
-        let binding = directory_path.clone();
-        let directory_path_display = binding.display();
-        let path = directory_path.join(login);
-        let display = path.display();
+        // Secrets other than the password would be saved back empty, so they have to decrypt.
+        let custom_fields: Result<Vec<String>, CryptoError> = entry
+            .custom_fields
+            .iter()
+            .map(|field| {
+                if field.secret {
+                    let value = self.decode_password(&field.value)?;
+                    Ok(format!("!{} = {}", field.name, value))
+                } else {
+                    Ok(format!("{} = {}", field.name, field.value))
+                }
+            })
+            .collect();
+        let totp = entry.totp.as_ref().map_or(Ok(String::new()), |totp| {
+            let secret = self.decode_password(&totp.secret)?;
+            Ok(totp::format_setup(&secret, &totp.parameters))
+        });
+        let (custom_fields, totp) = match (custom_fields, totp) {
+            (Ok(custom_fields), Ok(totp)) => (custom_fields.join("\n"), totp),
+            (Err(why), _) | (_, Err(why)) => {
+                self.error_banner = Some(format!(
+                    "Couldn't decrypt the secrets of \"{}\", so it can't be edited: {why}",
+                    entry.label
+                ));
+                self.password_list.items[password_index].is_corrupted = true;
+                return;
+            }
+        };
 
-        if !directory_path.exists() {
-            match fs::create_dir_all(directory_path) {
-                Err(why) => panic!("couldn't create {}: {}", directory_path_display, why),
-                Ok(_) => {}
+        self.open_form(DisplayInputs::EditSelected(password_index));
+
+        // An entry whose password no longer decrypts can still be renamed or given a new one.
+        let password = self.decode_password(&entry.password).unwrap_or_default();
+
+        self.form.set_value(FormField::Service, entry.label);
+        self.form.set_value(FormField::Username, entry.username);
+        self.form.set_value(FormField::Password, password);
+        self.form.set_value(FormField::Urls, entry.urls.join(", "));
+        self.form.set_value(FormField::Tags, entry.tags.join(", "));
+        self.form.set_value(FormField::Totp, totp);
+        self.form.set_value(FormField::Notes, entry.notes);
+        self.form.set_value(FormField::CustomFields, custom_fields);
+    }
+
+    /// Copies the form into `entry`, sealing the password and secret custom fields.
+    ///
+    /// An empty password keeps the one already sealed in `entry`, if any, and secrets that
+    /// weren't changed keep their sealed value.
+    fn apply_form(&self, entry: &mut Entry) -> Result<(), String> {
+        let password = self.form.value(FormField::Password);
+        if !password.is_empty() {
+            entry.password = self
+                .encode_password(&password)
+                .map_err(|why| format!("Couldn't encrypt the password: {why}"))?;
+            entry.strength = Some(strength::estimate(&password).score);
+        }
+
+        let mut custom_fields = vec![];
+        for (index, line) in self.form.value(FormField::CustomFields).lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
             }
+
+            let Some((name, value)) = line.split_once('=') else {
+                return Err(format!(
+                    "Custom field on line {} should look like `name = value`",
+                    index + 1
+                ));
+            };
+            let (secret, name) = match name.trim().strip_prefix('!') {
+                Some(name) => (true, name.trim()),
+                None => (false, name.trim()),
+            };
+            let unchanged = entry.custom_fields.iter().find(|field| {
+                field.secret
+                    && field.name == name
+                    && self.decode_password(&field.value).ok().as_deref() == Some(value.trim())
+            });
+            let value = if let Some(unchanged) = unchanged.filter(|_| secret) {
+                unchanged.value.clone()
+            } else if secret {
+                self.encode_password(value.trim())
+                    .map_err(|why| format!("Couldn't encrypt \"{name}\": {why}"))?
+            } else {
+                value.trim().to_string()
+            };
+
+            custom_fields.push(CustomField {
+                name: name.to_string(),
+                value,
+                secret,
+            });
         }
 
-        if !path.exists() {
-            match File::create(&path) {
-                Ok(_) => {}
-                Err(why) => panic!("couldn't create {}: {}", display, why),
+        let setup = self.form.value(FormField::Totp);
+        entry.totp = if setup.trim().is_empty() {
+            None
+        } else {
+            let (secret, parameters) =
+                totp::parse_setup(&setup).map_err(|why| format!("Invalid TOTP secret: {why}"))?;
+
+            match entry.totp.take() {
+                Some(totp)
+                    if totp.parameters == parameters
+                        && self.decode_password(&totp.secret).ok() == Some(secret.clone()) =>
+                {
+                    Some(totp)
+                }
+                _ => {
+                    let secret = self
+                        .encode_password(&secret)
+                        .map_err(|why| format!("Couldn't encrypt the TOTP secret: {why}"))?;
+
+                    Some(OneTimePassword { secret, parameters })
+                }
             }
+        };
+
+        entry.label = self.form.value(FormField::Service);
+        entry.username = self.form.value(FormField::Username).trim().to_string();
+        entry.urls = split_list(&self.form.value(FormField::Urls));
+        entry.tags = split_list(&self.form.value(FormField::Tags));
+        entry.notes = self.form.value(FormField::Notes);
+        entry.custom_fields = custom_fields;
+
+        Ok(())
+    }
+
+    fn confirm(&mut self, confirmation: Confirmation) {
+        match confirmation {
+            Confirmation::Delete(password_index) => self.delete_entry(password_index),
+            Confirmation::Purge(trash_index) => self.purge_entry(trash_index),
+        }
+    }
+
+    fn delete_entry(&mut self, password_index: usize) {
+        if password_index >= self.password_list.items.len() {
+            return;
         }
 
-        let mut file = match File::open(&path) {
-            Err(why) => panic!("couldn't open {}: {}", display, why),
-            Ok(file) => file,
+        let item = self.password_list.remove(password_index);
+        self.last_deletion = Some((password_index, item.entry.clone()));
+        self.trash_list.push(item);
+
+        self.save_passwords();
+    }
+
+    fn undo_deletion(&mut self) {
+        let Some((password_index, entry)) = self.last_deletion.take() else {
+            return;
+        };
+
+        // The entry may have been restored or purged from the trash view since.
+        let Some(trash_index) = self
+            .trash_list
+            .items
+            .iter()
+            .rposition(|item| item.entry == entry)
+        else {
+            self.error_banner = Some(String::from("The deleted entry is no longer in the trash"));
+            return;
+        };
+
+        let item = self.trash_list.remove(trash_index);
+        let password_index = password_index.min(self.password_list.items.len());
+        self.password_list.insert(password_index, item);
+
+        self.save_passwords();
+    }
+
+    fn restore_selected(&mut self) {
+        let Some(trash_index) = self.trash_list.selected() else {
+            return;
         };
 
-        // Read the file contents into a string, returns `io::Result<usize>`
-        let mut s = String::new();
-        match file.read_to_string(&mut s) {
-            Err(why) => panic!("couldn't read {}: {}", display, why),
-            Ok(_) => {}
+        let item = self.trash_list.remove(trash_index);
+        self.password_list.push(item);
+
+        self.save_passwords();
+    }
+
+    fn purge_entry(&mut self, trash_index: usize) {
+        if trash_index >= self.trash_list.items.len() {
+            return;
         }
 
-        let encrypted_passwords: Vec<(&str, &str)> = s
-            .lines()
-            .map(|line| {
-                let results: Vec<&str> = line.split('=').collect();
-                (results[0], results[1])
-            })
-            .collect();
+        self.trash_list.remove(trash_index);
+
+        self.save_passwords();
+    }
+
+    fn shown_list(&mut self) -> &mut PasswordList {
+        if self.show_trash {
+            &mut self.trash_list
+        } else {
+            &mut self.password_list
+        }
+    }
+
+    /// Narrows the password list down as the query is typed.
+    fn type_search(&mut self, key_code: KeyCode) {
+        let mut query = self.password_list.query().unwrap_or_default().to_string();
+        match key_code {
+            KeyCode::Backspace => {
+                query.pop();
+            }
+            KeyCode::Char(c) => query.push(c),
+            _ => return,
+        }
 
-        self.password_list = PasswordList::from(encrypted_passwords);
-        let item = PasswordListItem {
-            encrypted_value: BASE64_STANDARD.encode(crypto_utils::encrypt(
-                "test",
-                self.user_password.expose_secret(),
-            )),
-            label: String::from("facebook"),
+        self.password_list.search(&query);
+    }
+
+    /// Stops typing the query, keeping the results unless the query is empty.
+    fn finish_search(&mut self) {
+        self.searching = false;
+        if self.password_list.query() == Some("") {
+            self.password_list.clear_search();
+        }
+    }
+
+    fn select_next(&mut self) {
+        self.revealed = None;
+        self.shown_list().state.select_next();
+    }
+    fn select_previous(&mut self) {
+        self.revealed = None;
+        self.shown_list().state.select_previous();
+    }
+
+    /// Forgets the master key and everything decrypted with it, and hands over to the lock
+    /// screen.
+    fn lock(&mut self, state: &mut AppState) {
+        // Dropping the vault zeroizes the key.
+        let Some(vault) = self.vault.take() else {
+            return;
+        };
+        self.message_bus
+            .borrow_mut()
+            .submit_message(Message::Locked(vault.login().to_string()));
+        drop(vault);
+
+        let _ = self.clipboard.clear_now();
+        self.locked_selection = Some(LockedSelection {
+            password: self.password_list.selected(),
+            trash: self.trash_list.selected(),
+            show_trash: self.show_trash,
+        });
+
+        for list in [&mut self.password_list, &mut self.trash_list] {
+            for item in list.items.iter_mut() {
+                item.entry.zeroize();
+            }
+        }
+        if let Some((_, entry)) = self.last_deletion.as_mut() {
+            entry.zeroize();
+        }
+        self.password_list = PasswordList::from(Vec::new());
+        self.trash_list = PasswordList::from(Vec::new());
+        self.last_deletion = None;
+        self.revealed = None;
+        self.history = None;
+        self.close_form();
+        self.pending_confirmation = None;
+        self.searching = false;
+        self.show_trash = false;
+        self.error_banner = None;
+        self.notice = None;
+
+        *state = AppState::Locked;
+    }
+
+    /// Selects what was selected before the vault was locked.
+    fn restore_selection(&mut self) {
+        let Some(selection) = self.locked_selection.take() else {
+            return;
         };
 
-        self.password_list.items.push(item);
+        if let Some(index) = selection.password {
+            self.password_list.select_item(index);
+        }
+        if let Some(index) = selection.trash {
+            self.trash_list.select_item(index);
+        }
+        self.show_trash = selection.show_trash;
+    }
 
-        if self.password_list.items.len() > 0 {
-            self.password_list.state = ListState::default().with_selected(Some(1));
+    fn load_passwords(&mut self, entries: Entries) {
+        self.password_list = PasswordList::from(entries.active);
+        self.trash_list = PasswordList::from(entries.trash);
+        self.last_deletion = None;
+
+        let mut corrupted_count = 0;
+        for index in 0..self.password_list.items.len() {
+            let entry = &self.password_list.items[index].entry;
+            if entry.has_password() && self.decode_password(entry.password.as_str()).is_err() {
+                self.password_list.items[index].is_corrupted = true;
+                corrupted_count += 1;
+            }
         }
+
+        let damaged = self.vault.as_ref().map_or(&[][..], Vault::damaged);
+
+        self.error_banner = if let Some(first_damaged) = damaged.first() {
+            Some(format!(
+                "Skipped {} unreadable entries ({first_damaged}), they are kept as they are",
+                damaged.len()
+            ))
+        } else if corrupted_count > 0 {
+            Some(format!(
+                "{corrupted_count} entries could not be decrypted and may have been tampered with"
+            ))
+        } else {
+            None
+        };
+
+        self.searching = false;
+        self.revealed = None;
+        self.password_list.select_row(0);
+        self.trash_list.select_row(0);
     }
 
-    fn clear_inputs(&mut self) {
-        self.service_input.clear_value();
-        self.password_input.clear_value();
+    fn save_passwords(&mut self) {
+        let entries = Zeroizing::new(Entries {
+            active: entries(&self.password_list),
+            trash: entries(&self.trash_list),
+        });
+
+        let Some(vault) = self.vault.as_mut() else {
+            return;
+        };
+
+        if let Err(why) = vault.save(&mut *self.vault_store.borrow_mut(), &entries) {
+            self.error_banner = Some(format!("Couldn't save the vault: {why}"));
+        }
     }
 
     fn submit_generate_password(&mut self) {
-        let new_password = Alphanumeric.sample_string(&mut rand::thread_rng(), 20);
-        let encoded = BASE64_STANDARD.encode(crypto_utils::encrypt(
-            new_password.as_str(),
-            self.user_password.expose_secret(),
-        ));
+        let (encoded, score) = match self.generator.password() {
+            Ok(new_password) => (
+                self.encode_password(new_password),
+                strength::estimate(new_password).score,
+            ),
+            Err(why) => {
+                self.error_banner = Some(format!("Couldn't generate a password: {why}"));
+                return;
+            }
+        };
+        let encoded = match encoded {
+            Ok(encoded) => encoded,
+            Err(why) => {
+                self.error_banner = Some(format!("Couldn't encrypt the password: {why}"));
+                return;
+            }
+        };
+
+        let mut entry = Entry::new(self.generator.service(), encoded);
+        entry.strength = Some(score);
+        self.password_list.push(PasswordListItem::from(entry));
 
-        let service_name = self.service_input.get_value();
-        self.password_list.items.push(PasswordListItem::from((
-            service_name.clone(),
-            encoded.clone(),
-        )));
+        self.save_passwords();
+        self.save_generator_options();
+        self.close_form();
+    }
 
-        #[cfg(not(debug_assertions))]
-        let directory_base_path = Path::new(PASSWORD_PATH).join("rusty-lock");
+    fn save_generator_options(&mut self) {
+        self.generator_options = self.generator.options().clone();
 
-        #[cfg(debug_assertions)]
-        let directory_base_path = Path::new(PASSWORD_PATH);
+        let Some(vault) = self.vault.as_ref() else {
+            return;
+        };
+        let saved = self
+            .vault_store
+            .borrow_mut()
+            .save_preferences(vault.login(), &self.generator_options.serialize());
 
-        let directory_path = directory_base_path.join("pwds").join("asd");
+        if let Err(why) = saved {
+            self.error_banner = Some(format!("Couldn't remember the generator options: {why}"));
+        }
+    }
 
-        let mut file = OpenOptions::new()
-            .write(true)
-            .append(true)
-            .open(directory_path)
-            .unwrap();
+    fn submit_edit_password(&mut self, password_index: usize) {
+        let Some(item) = self.password_list.items.get(password_index) else {
+            return;
+        };
 
-        if let Err(e) = writeln!(file, "{service_name}={encoded}") {
-            eprintln!("Couldn't write to file: {}", e);
+        let mut entry = item.entry.clone();
+        if let Err(why) = self.apply_form(&mut entry) {
+            self.error_banner = Some(why);
+            return;
+        }
+        entry.touch();
+
+        // The form always seals the password anew, so compare what it decrypts to.
+        let previous = &item.entry.password;
+        let changed = self.decode_password(previous).ok()
+            != Some(self.form.value(FormField::Password))
+            && previous != &entry.password;
+        if changed && !previous.is_empty() {
+            entry.remember_password(previous.clone(), self.settings.history_depth);
         }
 
-        self.clear_inputs();
+        let item = &mut self.password_list.items[password_index];
+        item.is_corrupted &= item.entry.password == entry.password;
+        item.entry = entry;
+        self.password_list.edited(password_index);
 
-        self.display_inputs = None;
-        self.active_input = None;
+        self.save_passwords();
+        self.close_form();
     }
 
     fn submit_import_password(&mut self) {
-        if let Some(active) = self.active_input {
-            match active {
-                CurrentlyActiveInput::Service => self.focus_password(),
-                CurrentlyActiveInput::Password => {
-                    let encoded = BASE64_STANDARD.encode(crypto_utils::encrypt(
-                        self.password_input.get_value().as_str(),
-                        self.user_password.expose_secret(),
-                    ));
+        let mut entry = Entry::new(String::new(), String::new());
+        if let Err(why) = self.apply_form(&mut entry) {
+            self.error_banner = Some(why);
+            return;
+        }
 
-                    let service_name = self.service_input.get_value();
-                    self.password_list.items.push(PasswordListItem::from((
-                        service_name.clone(),
-                        encoded.clone(),
-                    )));
+        self.password_list.push(PasswordListItem::from(entry));
 
-                    #[cfg(not(debug_assertions))]
-                    let directory_base_path = Path::new(PASSWORD_PATH).join("rusty-lock");
+        self.save_passwords();
+        self.close_form();
+    }
 
-                    #[cfg(debug_assertions)]
-                    let directory_base_path = Path::new(PASSWORD_PATH);
+    /// Reads one-time passwords from the URI or file given in the form, attaching each one to
+    /// the entry of its service and account, or to a new entry if there is none.
+    fn submit_import_otp(&mut self) {
+        let source = self.form.value(FormField::OtpSource).trim().to_string();
+        let text = if source.starts_with("otpauth") {
+            source
+        } else {
+            // What was typed may be a pasted secret rather than a path, so it is never echoed.
+            if !fs::metadata(&source).is_ok_and(|metadata| metadata.is_file()) {
+                self.error_banner = Some(String::from(
+                    "Enter an otpauth:// URI or the path of a file of them",
+                ));
+                return;
+            }
+            match fs::read_to_string(&source) {
+                Ok(text) => text,
+                Err(why) => {
+                    self.error_banner = Some(format!("Couldn't read the file: {why}"));
+                    return;
+                }
+            }
+        };
 
-                    let directory_path = directory_base_path.join("pwds").join("asd");
+        let (accounts, errors) = otpauth::parse_lines(&text);
 
-                    let mut file = OpenOptions::new()
-                        .write(true)
-                        .append(true)
-                        .open(directory_path)
-                        .unwrap();
+        // Every secret is sealed before any entry changes, so a failure imports nothing.
+        let sealed: Result<Vec<String>, CryptoError> = accounts
+            .iter()
+            .map(|account| self.encode_password(&account.secret))
+            .collect();
+        let Ok(secrets) = sealed else {
+            self.error_banner = Some(String::from(
+                "Couldn't encrypt the imported secrets, nothing was imported",
+            ));
+            self.close_form();
+            return;
+        };
 
-                    if let Err(e) = writeln!(file, "{service_name}={encoded}") {
-                        eprintln!("Couldn't write to file: {}", e);
-                    }
+        let (mut attached, mut created) = (0, 0);
+        for (account, secret) in accounts.into_iter().zip(secrets) {
+            let totp = OneTimePassword {
+                secret,
+                parameters: account.parameters,
+            };
+            let service = if account.issuer.is_empty() {
+                account.account.clone()
+            } else {
+                account.issuer
+            };
+
+            match self.find_entry(&service, &account.account) {
+                Some(password_index) => {
+                    let entry = &mut self.password_list.items[password_index].entry;
+                    entry.totp = Some(totp);
+                    entry.touch();
+                    attached += 1;
+                }
+                None => {
+                    let mut entry = Entry::new(service, String::new());
+                    entry.username = account.account;
+                    entry.totp = Some(totp);
+                    self.password_list.push(PasswordListItem::from(entry));
+                    created += 1;
+                }
+            }
+        }
+
+        let summary = format!(
+            "Imported {} one-time passwords: {attached} added to existing entries, {created} new",
+            attached + created
+        );
+        match errors.first() {
+            Some((line, why)) => {
+                self.error_banner = Some(format!(
+                    "{summary}. Skipped {} lines (line {line}: {why})",
+                    errors.len()
+                ))
+            }
+            None => self.notice = Some(summary),
+        }
+
+        self.save_passwords();
+        self.close_form();
+    }
+
+    /// The entry for `account` at `service`: the same label, and the same username or none.
+    fn find_entry(&self, service: &str, account: &str) -> Option<usize> {
+        let items = &self.password_list.items;
+        let same_service = |entry: &Entry| entry.label.eq_ignore_ascii_case(service);
+
+        items
+            .iter()
+            .position(|item| {
+                same_service(&item.entry) && item.entry.username.eq_ignore_ascii_case(account)
+            })
+            .or_else(|| {
+                items
+                    .iter()
+                    .position(|item| same_service(&item.entry) && item.entry.username.is_empty())
+            })
+    }
 
-                    self.clear_inputs();
-                    self.display_inputs = None;
-                    self.active_input = None;
+    /// The password of `entry`, masked unless it was revealed, with how long it stays readable.
+    fn password_line(&self, entry: &Entry) -> Line<'static> {
+        let revealed = self
+            .revealed
+            .as_ref()
+            .filter(|revealed| revealed.sealed == entry.password);
+
+        let mut spans = vec![String::from("Password: ").bold()];
+        if !entry.has_password() {
+            spans.push(String::from("none").dark_gray());
+            return Line::from(spans);
+        }
+
+        match revealed {
+            Some(revealed) => {
+                spans.push(revealed.password.expose_secret().to_string().yellow());
+                if let Some(deadline) = revealed.deadline {
+                    let left = deadline.saturating_duration_since(Instant::now());
+                    // Rounded up, so the countdown ends at 1 rather than 0.
+                    let seconds = (left + Duration::from_millis(999)).as_secs();
+                    spans.push(format!(" (masked in {seconds}s)").dark_gray());
                 }
             }
+            None => spans.push(String::from("********").into()),
+        }
+
+        Line::from(spans)
+    }
+
+    /// The current code of `totp` and the seconds until it changes, which HOTP codes never do.
+    fn current_code(&self, totp: &OneTimePassword) -> Result<(String, Option<u64>), String> {
+        let secret = self
+            .decode_password(&totp.secret)
+            .map_err(|why| format!("Couldn't decrypt the TOTP secret: {why}"))?;
+        let secret = totp::decode_base32(&secret)
+            .map_err(|why| format!("The TOTP secret is damaged: {why}"))?;
+        let now = u64::try_from(Utc::now().timestamp()).unwrap_or_default();
+
+        let seconds_left = totp.parameters.counter.is_none();
+
+        Ok((
+            totp::code(&secret, &totp.parameters, now),
+            seconds_left.then(|| totp::seconds_left(&totp.parameters, now)),
+        ))
+    }
+
+    /// Copies the current code of the selected entry, moving HOTP generators on to the next one.
+    fn copy_code(&mut self) {
+        let Some(password_index) = self.password_list.selected() else {
+            return;
+        };
+        let entry = &self.password_list.items[password_index].entry;
+        let Some(totp) = &entry.totp else {
+            self.error_banner = Some(format!("\"{}\" has no TOTP secret", entry.label));
+            return;
+        };
+
+        let copied = self.current_code(totp).and_then(|(code, _)| {
+            self.clipboard.copy_secret(&code).map_err(|why| {
+                format!(
+                    "Couldn't copy with {}: {}",
+                    self.clipboard.backend_name(),
+                    why
+                )
+            })
+        });
+
+        if let Err(why) = copied {
+            self.error_banner = Some(why);
+            return;
+        }
+
+        // A HOTP code is only accepted once, so the next copy has to give the next one.
+        let entry = &mut self.password_list.items[password_index].entry;
+        if let Some(counter) = entry
+            .totp
+            .as_mut()
+            .and_then(|totp| totp.parameters.counter.as_mut())
+        {
+            *counter += 1;
+            entry.touch();
+            self.save_passwords();
+        }
+    }
+
+    /// The current code of `entry` with a bar counting down to the next one, or the counter of
+    /// HOTP generators.
+    fn code_line(&self, totp: &OneTimePassword) -> Line<'static> {
+        const BAR_WIDTH: u64 = 15;
+        let name = match totp.parameters.counter {
+            Some(_) => String::from("HOTP: ").bold(),
+            None => String::from("TOTP: ").bold(),
+        };
+
+        let (code, seconds_left) = match self.current_code(totp) {
+            Ok(current) => current,
+            Err(why) => return Line::from(vec![name, why.red()]),
+        };
+        let (first, second) = code.split_at(code.len() / 2);
+        let Some(seconds_left) = seconds_left else {
+            let counter = totp.parameters.counter.unwrap_or_default();
+            return Line::from(vec![
+                name,
+                format!("{first} {second} ").yellow().bold(),
+                format!("(counter {counter})").dark_gray(),
+            ]);
+        };
+
+        let period = totp.parameters.period;
+        let filled = (seconds_left * BAR_WIDTH).div_ceil(period);
+        let color = if seconds_left <= 5 {
+            Color::Red
+        } else {
+            Color::Green
+        };
+
+        Line::from(vec![
+            name,
+            format!("{first} {second} ").yellow().bold(),
+            "█".repeat(filled as usize).fg(color),
+            "░".repeat((BAR_WIDTH - filled) as usize).dark_gray(),
+            format!(" {seconds_left}s").into(),
+        ])
+    }
+
+    /// Every field of `entry`, with the password masked unless it was revealed and secret
+    /// custom fields masked.
+    fn details(&self, entry: &Entry) -> Text<'static> {
+        let field =
+            |name: &str, value: String| Line::from(vec![format!("{name}: ").bold(), value.into()]);
+        let time = |time: Option<DateTime<Utc>>| {
+            time.map_or(String::from("unknown"), |time| {
+                time.with_timezone(&Local)
+                    .format("%Y-%m-%d %H:%M")
+                    .to_string()
+            })
+        };
+
+        let mut lines = vec![
+            field("Service", entry.label.clone()),
+            field("Username", entry.username.clone()),
+            self.password_line(entry),
+        ];
+        if let Some(totp) = &entry.totp {
+            lines.push(self.code_line(totp));
+        }
+        lines.extend([
+            field("URLs", entry.urls.join(", ")),
+            field("Tags", entry.tags.join(", ")),
+        ]);
+        if entry.has_password() {
+            lines.push(field(
+                "Strength",
+                entry.strength.map_or(String::from("unknown"), |score| {
+                    format!("{} ({score}/{MAX_SCORE})", strength::score_label(score))
+                }),
+            ));
+        }
+
+        for custom_field in &entry.custom_fields {
+            let value = if custom_field.secret {
+                String::from("********")
+            } else {
+                custom_field.value.clone()
+            };
+            lines.push(field(&custom_field.name, value));
+        }
+
+        lines.push(field("Created", time(entry.created)));
+        lines.push(field("Modified", time(entry.modified)));
+        lines.push(field("Last used", time(entry.last_used)));
+
+        if !entry.notes.is_empty() {
+            lines.push(Line::default());
+            lines.push(Line::from("Notes:".bold()));
+            lines.extend(entry.notes.lines().map(|line| Line::from(line.to_string())));
         }
+
+        Text::from(lines)
     }
 }
 
@@ -323,18 +1042,85 @@ impl Screen for Dashboard {
         event: crossterm::event::Event,
         state: &mut crate::app::AppState,
     ) {
-        if let Some(display_inputs) = self.display_inputs {
-            match event {
-                Event::Key(key) if key.kind == KeyEventKind::Press => {
-                    self.handle_input_field_event(key.code);
+        if let Event::Key(key) = event {
+            self.notice = None;
+            // Mouse moves, focus changes and resizes happen without anyone at the keyboard.
+            if key.kind == KeyEventKind::Press {
+                self.last_activity = Instant::now();
+            }
+        }
 
-                    if key.code == KeyCode::Enter {
+        if let Some(confirmation) = self.pending_confirmation {
+            match event {
+                Event::Key(key) if key.kind == KeyEventKind::Press => match key.code {
+                    KeyCode::Char('y') | KeyCode::Enter => {
+                        self.pending_confirmation = None;
+                        self.confirm(confirmation);
+                    }
+                    KeyCode::Char('n') | KeyCode::Esc => self.pending_confirmation = None,
+                    _ => {}
+                },
+                _ => {}
+            }
+        } else if let Some(display_inputs) = self.display_inputs {
+            match event {
+                Event::Key(key) if key.kind == KeyEventKind::Press => match key.code {
+                    KeyCode::Esc => self.close_form(),
+                    KeyCode::Enter if !key.modifiers.contains(KeyModifiers::ALT) => {
                         match display_inputs {
                             DisplayInputs::GeneratePassword => self.submit_generate_password(),
                             DisplayInputs::ImportPassword => self.submit_import_password(),
+                            DisplayInputs::ImportOtp => self.submit_import_otp(),
+                            DisplayInputs::EditSelected(password_index) => {
+                                self.submit_edit_password(password_index)
+                            }
                         }
                     }
-                }
+                    _ => match display_inputs {
+                        DisplayInputs::GeneratePassword => self.generator.handle_key(key),
+                        _ => self.form.handle_key(key),
+                    },
+                },
+                _ => {}
+            }
+        } else if let Some(history) = self.history.as_mut() {
+            match event {
+                Event::Key(key) if key.kind == KeyEventKind::Press => match key.code {
+                    KeyCode::Down => history.select_next(),
+                    KeyCode::Up => history.select_previous(),
+                    KeyCode::Char('c') => self.copy_from_history(),
+                    KeyCode::Char('h') | KeyCode::Esc => self.history = None,
+                    _ => {}
+                },
+                _ => {}
+            }
+        } else if self.searching {
+            match event {
+                Event::Key(key) if key.kind == KeyEventKind::Press => match key.code {
+                    KeyCode::Down => self.select_next(),
+                    KeyCode::Up => self.select_previous(),
+                    KeyCode::Enter => self.finish_search(),
+                    KeyCode::Esc => {
+                        self.searching = false;
+                        self.password_list.clear_search();
+                    }
+                    key_code => self.type_search(key_code),
+                },
+                _ => {}
+            }
+        } else if self.show_trash {
+            match event {
+                Event::Key(key) if key.kind == KeyEventKind::Press => match key.code {
+                    KeyCode::Down => self.select_next(),
+                    KeyCode::Up => self.select_previous(),
+                    KeyCode::Char('r') => self.restore_selected(),
+                    KeyCode::Char('p') => {
+                        self.pending_confirmation =
+                            self.trash_list.selected().map(Confirmation::Purge);
+                    }
+                    KeyCode::Char('t') | KeyCode::Esc => self.show_trash = false,
+                    _ => {}
+                },
                 _ => {}
             }
         } else {
@@ -342,30 +1128,35 @@ impl Screen for Dashboard {
                 Event::Key(key) if key.kind == KeyEventKind::Press => match key.code {
                     KeyCode::Down => self.select_next(),
                     KeyCode::Up => self.select_previous(),
-                    KeyCode::Char('c') => {
-                        if let Some(password_index) = self.password_list.state.selected() {
-                            let encoded_password = self
-                                .password_list
-                                .items
-                                .get(password_index)
-                                .unwrap()
-                                .encrypted_value
-                                .as_str();
-
-                            let decoded_password = self.decode_password(encoded_password);
-
-                            self.copy_to_clipboard(decoded_password.as_str());
-                        }
+                    KeyCode::Char('c') => self.copy_selected(),
+                    KeyCode::Char('i') => self.show_details = !self.show_details,
+                    KeyCode::Char('v') => self.toggle_reveal(),
+                    KeyCode::Char('o') => self.copy_code(),
+                    KeyCode::Char('d') => {
+                        self.pending_confirmation =
+                            self.password_list.selected().map(Confirmation::Delete);
                     }
-                    KeyCode::Char('g') => {
-                        self.display_inputs = Some(DisplayInputs::GeneratePassword);
-                        self.focus_service();
+                    KeyCode::Char('h') => self.show_history(),
+                    KeyCode::Char('u') => self.undo_deletion(),
+                    KeyCode::Char('t') => self.show_trash = true,
+                    KeyCode::Char('e') => self.edit_selected(),
+                    KeyCode::Char('/') => {
+                        self.searching = true;
+                        if self.password_list.query().is_none() {
+                            self.password_list.search("");
+                        }
                     }
-                    KeyCode::Char('n') => {
-                        self.display_inputs = Some(DisplayInputs::ImportPassword);
-                        self.focus_service();
+                    KeyCode::Esc if self.password_list.query().is_some() => {
+                        self.password_list.clear_search()
                     }
+                    KeyCode::Esc => self.error_banner = None,
+                    KeyCode::Char('g') => self.open_form(DisplayInputs::GeneratePassword),
+                    KeyCode::Char('n') => self.open_form(DisplayInputs::ImportPassword),
+                    KeyCode::Char('a') => self.open_form(DisplayInputs::ImportOtp),
+                    KeyCode::Char('l') => self.lock(state),
                     KeyCode::Char('q') => {
+                        // Secrets should not outlive the session on the clipboard.
+                        let _ = self.clipboard.clear_now();
                         *state = AppState::Quit;
                     }
                     _ => {}
@@ -379,12 +1170,25 @@ impl Screen for Dashboard {
         let area = frame.area();
         let buf = frame.buffer_mut();
 
-        let title = Title::from(" List of passwords ".bold());
+        let title = if self.show_trash {
+            Title::from(" Trash ".bold())
+        } else {
+            Title::from(" List of passwords ".bold())
+        };
         let block = Block::bordered()
             .title(title.alignment(Alignment::Center))
             .border_set(border::THICK);
 
-        let text = Text::from("View and add or copy all your passwords from this screen!");
+        let text = match &self.error_banner {
+            Some(error_banner) => Text::from(error_banner.as_str().red().bold()),
+            None if self.notice.is_some() => {
+                Text::from(self.notice.as_deref().unwrap_or_default().green().bold())
+            }
+            None if self.show_trash => {
+                Text::from("Restore deleted passwords or purge them for good from this screen")
+            }
+            None => Text::from("View and add or copy all your passwords from this screen!"),
+        };
 
         let layout_parts = Layout::default()
             .direction(Direction::Vertical)
@@ -402,10 +1206,68 @@ impl Screen for Dashboard {
 
         let block = Block::bordered().border_set(border::THICK);
 
-        let items: Vec<ListItem> = self
-            .password_list
-            .items
-            .iter()
+        let mut list_area = layout_parts[1];
+        if self.show_details {
+            let [list, details] =
+                Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
+                    .areas(list_area);
+            list_area = list;
+
+            let shown_list = if self.show_trash {
+                &self.trash_list
+            } else {
+                &self.password_list
+            };
+            let text = match shown_list.selected() {
+                Some(index) => self.details(&shown_list.items[index].entry),
+                None => Text::from("Nothing selected".dark_gray()),
+            };
+            Paragraph::new(text)
+                .block(
+                    Block::bordered()
+                        .title(Title::from(" Details ".bold()).alignment(Alignment::Center))
+                        .border_set(border::THICK),
+                )
+                .wrap(Wrap { trim: false })
+                .render(details, buf);
+        }
+
+        let query = self.password_list.query().filter(|_| !self.show_trash);
+        let mut search_cursor = None;
+        if let Some(query) = query {
+            let [search_area, rest] =
+                Layout::vertical([Constraint::Length(3), Constraint::Min(1)]).areas(list_area);
+            list_area = rest;
+
+            let count = format!(
+                " {} of {} ",
+                self.password_list.shown_count(),
+                self.password_list.items.len()
+            );
+            Paragraph::new(format!("/{query}"))
+                .block(
+                    Block::bordered()
+                        .title(" Search ".bold())
+                        .title(Title::from(count).alignment(Alignment::Right))
+                        .border_set(border::ROUNDED),
+                )
+                .render(search_area, buf);
+
+            if self.searching {
+                let typed = Line::from(format!("/{query}")).width() as u16;
+                search_cursor = Some(Position::new(search_area.x + 1 + typed, search_area.y + 1));
+            }
+        }
+
+        let shown_list = if self.show_trash {
+            &mut self.trash_list
+        } else {
+            &mut self.password_list
+        };
+
+        let items: Vec<ListItem> = shown_list
+            .list_items()
+            .into_iter()
             .enumerate()
             .map(|(i, item)| {
                 let color = if i % 2 == 0 {
@@ -414,7 +1276,7 @@ impl Screen for Dashboard {
                     Color::Blue
                 };
 
-                ListItem::from(item).fg(color)
+                item.fg(color)
             })
             .collect();
 
@@ -424,68 +1286,645 @@ impl Screen for Dashboard {
             .highlight_symbol(">")
             .highlight_spacing(HighlightSpacing::Always);
 
-        StatefulWidget::render(list, layout_parts[1], buf, &mut self.password_list.state);
-
-        Paragraph::new(
-            Line::from(vec![
+        StatefulWidget::render(list, list_area, buf, &mut shown_list.state);
+
+        let shortcuts = if let Some(DisplayInputs::GeneratePassword) = self.display_inputs {
+            vec![
+                "<Tab> ".bold(),
+                "Next option / ".into(),
+                "<Space> ".bold(),
+                "Toggle / ".into(),
+                "<Left/Right> ".bold(),
+                "Change / ".into(),
+                "<Ctrl+R> ".bold(),
+                "Regenerate / ".into(),
+                "<Enter> ".bold(),
+                "Save / ".into(),
+                "<Esc> ".bold(),
+                "Cancel".into(),
+            ]
+        } else if self.display_inputs.is_some() {
+            vec![
+                "<Tab> ".bold(),
+                "Next field / ".into(),
+                "<Shift+Tab> ".bold(),
+                "Previous field / ".into(),
+                "<Alt+Enter> ".bold(),
+                "New line / ".into(),
+                "<Enter> ".bold(),
+                "Save / ".into(),
+                "<Esc> ".bold(),
+                "Cancel".into(),
+            ]
+        } else if self.history.is_some() {
+            vec![
                 "<Down> ".bold(),
                 "Select below / ".into(),
                 "<Up> ".bold(),
                 "Select above / ".into(),
                 "<C> ".bold(),
+                "Copy old password / ".into(),
+                "<Esc> ".bold(),
+                "Back to passwords".into(),
+            ]
+        } else if self.searching {
+            vec![
+                "<Down> ".bold(),
+                "Select below / ".into(),
+                "<Up> ".bold(),
+                "Select above / ".into(),
+                "<Enter> ".bold(),
+                "Keep results / ".into(),
+                "<Esc> ".bold(),
+                "Clear search".into(),
+            ]
+        } else if self.show_trash {
+            vec![
+                "<Down> ".bold(),
+                "Select below / ".into(),
+                "<Up> ".bold(),
+                "Select above / ".into(),
+                "<R> ".bold(),
+                "Restore selected / ".into(),
+                "<P> ".bold(),
+                "Purge selected / ".into(),
+                "<T> ".bold(),
+                "Back to passwords".into(),
+            ]
+        } else {
+            vec![
+                "<Down> ".bold(),
+                "Select below / ".into(),
+                "<Up> ".bold(),
+                "Select above / ".into(),
+                "</> ".bold(),
+                "Search / ".into(),
+                "<C> ".bold(),
                 "Copy selected / ".into(),
+                "<O> ".bold(),
+                "Copy TOTP code / ".into(),
+                "<V> ".bold(),
+                "Reveal password / ".into(),
+                "<I> ".bold(),
+                "Toggle details / ".into(),
+                "<E> ".bold(),
+                "Edit selected / ".into(),
+                "<H> ".bold(),
+                "History / ".into(),
+                "<D> ".bold(),
+                "Delete selected / ".into(),
+                "<U> ".bold(),
+                "Undo delete / ".into(),
+                "<T> ".bold(),
+                "Trash / ".into(),
                 "<N> ".bold(),
                 "Add new / ".into(),
+                "<A> ".bold(),
+                "Import 2FA / ".into(),
                 "<G> ".bold(),
                 "Generate new / ".into(),
+                "<L> ".bold(),
+                "Lock / ".into(),
                 "<Q> ".bold(),
                 "Quit".into(),
-            ])
-            .style(Style::default().fg(Color::Green)),
-        )
-        .block(Block::default().borders(Borders::ALL))
-        .alignment(Alignment::Center)
-        .render(layout_parts[2], buf);
-
-        if let Some(display) = &self.display_inputs {
-            let input_area = Layout::default()
-                .direction(Direction::Vertical)
-                .horizontal_margin(20)
-                .flex(layout::Flex::Center)
-                .constraints([Constraint::Length(5), Constraint::Length(5)])
-                .split(layout_parts[1]);
+            ]
+        };
 
-            match display {
-                DisplayInputs::GeneratePassword => {
-                    self.service_input.render(input_area[0], buf);
-                }
-                DisplayInputs::ImportPassword => {
-                    self.service_input.render(input_area[0], buf);
-                    self.password_input.render(input_area[1], buf);
+        Paragraph::new(Line::from(shortcuts).style(Style::default().fg(Color::Green)))
+            .block(Block::default().borders(Borders::ALL))
+            .alignment(Alignment::Center)
+            .render(layout_parts[2], buf);
+
+        if let Some(history) = self.history.as_mut() {
+            if let Some(password_index) = self.password_list.selected() {
+                let entry = &self.password_list.items[password_index].entry;
+                let block = Block::bordered()
+                    .title(
+                        Title::from(format!(" Previous passwords of {} ", entry.label).bold())
+                            .alignment(Alignment::Center),
+                    )
+                    .border_set(border::THICK);
+
+                Clear.render(layout_parts[1], buf);
+                if entry.history.is_empty() {
+                    Paragraph::new("The password of this entry was never changed")
+                        .block(block)
+                        .alignment(Alignment::Center)
+                        .render(layout_parts[1], buf);
+                } else {
+                    let items: Vec<ListItem> = entry
+                        .history
+                        .iter()
+                        .map(|previous| {
+                            let replaced = previous.replaced.with_timezone(&Local);
+                            ListItem::new(format!(
+                                "******** replaced {}",
+                                replaced.format("%Y-%m-%d %H:%M")
+                            ))
+                        })
+                        .collect();
+                    let list = List::new(items)
+                        .block(block)
+                        .highlight_style(Style::default().add_modifier(Modifier::BOLD))
+                        .highlight_symbol(">")
+                        .highlight_spacing(HighlightSpacing::Always);
+
+                    StatefulWidget::render(list, layout_parts[1], buf, history);
                 }
             }
         }
 
-        if self.display_inputs.is_some() {
-            for input in [&self.service_input, &self.password_input].iter() {
-                if let Some(position) = input.cursor_position {
-                    frame.set_cursor_position(position);
-                }
+        if let Some(display) = &self.display_inputs {
+            let title = match display {
+                DisplayInputs::GeneratePassword => " Generate a password ",
+                DisplayInputs::ImportPassword => " New entry ",
+                DisplayInputs::ImportOtp => " Import one-time passwords ",
+                DisplayInputs::EditSelected(_) => " Edit entry ",
+            };
+            let block = Block::bordered()
+                .title(Title::from(title.bold()).alignment(Alignment::Center))
+                .border_set(border::THICK);
+            let form_area = block.inner(layout_parts[1]);
+
+            Clear.render(layout_parts[1], buf);
+            block.render(layout_parts[1], buf);
+            match display {
+                DisplayInputs::GeneratePassword => self.generator.render(form_area, buf),
+                _ => self.form.render(form_area, buf),
             }
         }
+
+        if let Some(confirmation) = self.pending_confirmation {
+            let dialog = match confirmation {
+                Confirmation::Delete(password_index) => ConfirmDialog {
+                    title: "Delete entry",
+                    question: format!(
+                        "Move \"{}\" to the trash?",
+                        self.password_list.items[password_index].entry.label
+                    ),
+                },
+                Confirmation::Purge(trash_index) => ConfirmDialog {
+                    title: "Purge entry",
+                    question: format!(
+                        "Delete \"{}\" for good? This cannot be undone.",
+                        self.trash_list.items[trash_index].entry.label
+                    ),
+                },
+            };
+
+            dialog.render(layout_parts[1], buf);
+        }
+
+        let cursor_position = match self.display_inputs {
+            Some(DisplayInputs::GeneratePassword) => self.generator.cursor_position(),
+            Some(_) => self.form.cursor_position(),
+            None => search_cursor,
+        };
+        if let Some(position) = cursor_position {
+            frame.set_cursor_position(position);
+        }
     }
 
     fn handle_messages(&mut self, messages: Vec<Message>, _state: &mut crate::app::AppState) {
         for message in messages {
             match message {
-                Message::LoginCredentials(login, password) => {
-                    let hash = crypto_utils::hash_password(password);
-
-                    self.user_password = SecretBox::new(Box::new(hash.to_vec()));
-
-                    self.load_passwords_from_file(login);
+                Message::RevealExpired => self.mask_if_expired(),
+                Message::Locked(_) => {}
+                Message::Unlocked(mut vault) => {
+                    let loaded = vault.load(&*self.vault_store.borrow());
+                    // Unreadable preferences only cost the user their last generator options.
+                    let preferences = self.vault_store.borrow().load_preferences(vault.login());
+                    self.generator_options = match preferences {
+                        Ok(Some(preferences)) => GeneratorOptions::parse(&preferences),
+                        _ => GeneratorOptions::default(),
+                    };
+                    self.vault = Some(vault);
+
+                    self.last_activity = Instant::now();
+
+                    match loaded {
+                        Ok(entries) => {
+                            self.load_passwords(entries);
+                            self.restore_selection();
+                        }
+                        Err(why) => {
+                            self.password_list = PasswordList::from(Vec::new());
+                            self.trash_list = PasswordList::from(Vec::new());
+                            self.error_banner = Some(format!("Couldn't open the vault: {why}"));
+                        }
+                    }
                 }
             }
         }
     }
+
+    fn handle_tick(&mut self, state: &mut crate::app::AppState) {
+        if let Err(why) = self.clipboard.clear_if_expired() {
+            self.error_banner = Some(format!("Couldn't clear the clipboard: {why}"));
+        }
+
+        let idle = self
+            .settings
+            .lock_timeout
+            .is_some_and(|timeout| self.last_activity.elapsed() >= timeout);
+        if idle {
+            self.lock(state);
+        }
+    }
+}
+
+fn entries(list: &PasswordList) -> Vec<Entry> {
+    list.items.iter().map(|item| item.entry.clone()).collect()
+}
+
+/// Splits a comma separated list, dropping blank items.
+fn split_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::KeyEvent;
+    use ratatui::backend::TestBackend;
+
+    use super::*;
+    use crate::{
+        clipboard::memory::MemoryClipboard,
+        vault::{self, memory_store::MemoryStore},
+    };
+
+    const LOGIN: &str = "alice";
+    const MASTER_PASSWORD: &str = "correct horse";
+
+    /// A dashboard unlocked on a fresh vault, with the store and clipboard it writes to.
+    fn unlocked_dashboard() -> (Dashboard, Rc<RefCell<MemoryStore>>, MemoryClipboard) {
+        let store = Rc::new(RefCell::new(MemoryStore::default()));
+        let vault = vault::create(
+            &mut *store.borrow_mut(),
+            LOGIN,
+            SecretString::from(MASTER_PASSWORD),
+        )
+        .unwrap();
+        let clipboard = MemoryClipboard::default();
+
+        let mut dashboard = Dashboard::new(
+            Rc::new(RefCell::new(MessageBus::new())),
+            Rc::clone(&store) as Rc<RefCell<dyn VaultStore>>,
+            Clipboard::new(Box::new(clipboard.clone()), None),
+            Settings::default(),
+        );
+        dashboard.handle_messages(vec![Message::Unlocked(vault)], &mut AppState::Dashboard);
+
+        (dashboard, store, clipboard)
+    }
+
+    /// Handles a key press and redraws, as the main loop does; inputs only place their cursor
+    /// when rendered.
+    fn press(dashboard: &mut Dashboard, code: KeyCode) {
+        let event = Event::Key(KeyEvent::new(code, KeyModifiers::NONE));
+        dashboard.handle_terminal_events(event, &mut AppState::Dashboard);
+
+        let mut terminal = Terminal::new(TestBackend::new(160, 50)).unwrap();
+        terminal.draw(|frame| dashboard.render(frame)).unwrap();
+    }
+
+    fn type_text(dashboard: &mut Dashboard, text: &str) {
+        for c in text.chars() {
+            press(dashboard, KeyCode::Char(c));
+        }
+    }
+
+    /// Adds an entry through the form, as typed in by hand.
+    fn add_entry(dashboard: &mut Dashboard, label: &str, username: &str, password: &str) {
+        press(dashboard, KeyCode::Char('n'));
+        type_text(dashboard, label);
+        press(dashboard, KeyCode::Down);
+        type_text(dashboard, username);
+        press(dashboard, KeyCode::Down);
+        type_text(dashboard, password);
+        press(dashboard, KeyCode::Enter);
+    }
+
+    /// What the store holds for the test account, read back with the master password.
+    fn saved_entries(store: &Rc<RefCell<MemoryStore>>) -> Entries {
+        let mut store = store.borrow_mut();
+        let mut vault =
+            vault::unlock(&mut *store, LOGIN, SecretString::from(MASTER_PASSWORD)).unwrap();
+
+        vault.load(&*store).unwrap()
+    }
+
+    #[test]
+    fn entries_added_through_the_form_are_saved_and_copied() {
+        let (mut dashboard, store, clipboard) = unlocked_dashboard();
+
+        add_entry(&mut dashboard, "mail", "alice@example.com", "hunter2!");
+
+        let saved = saved_entries(&store);
+        assert_eq!(saved.active.len(), 1);
+        assert_eq!(saved.active[0].label, "mail");
+        assert_eq!(saved.active[0].username, "alice@example.com");
+
+        press(&mut dashboard, KeyCode::Down);
+        press(&mut dashboard, KeyCode::Char('c'));
+        assert_eq!(clipboard.contents().as_deref(), Some("hunter2!"));
+    }
+
+    /// The RFC 4226 test secret, whose first codes are 755224, 287082 and 359152.
+    const HOTP_URI: &str =
+        "otpauth://hotp/Example:alice?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ&counter=0";
+
+    #[test]
+    fn copying_a_hotp_code_moves_on_to_the_next_one() {
+        let (mut dashboard, store, clipboard) = unlocked_dashboard();
+        press(&mut dashboard, KeyCode::Char('a'));
+        type_text(&mut dashboard, HOTP_URI);
+        press(&mut dashboard, KeyCode::Enter);
+
+        press(&mut dashboard, KeyCode::Down);
+        press(&mut dashboard, KeyCode::Char('o'));
+        assert_eq!(clipboard.contents().as_deref(), Some("755224"));
+        press(&mut dashboard, KeyCode::Char('o'));
+        assert_eq!(clipboard.contents().as_deref(), Some("287082"));
+
+        let saved = saved_entries(&store);
+        let totp = saved.active[0].totp.as_ref().unwrap();
+        assert_eq!(totp.parameters.counter, Some(2));
+    }
+
+    #[test]
+    fn long_migration_exports_are_imported_through_the_form() {
+        let (mut dashboard, store, _) = unlocked_dashboard();
+        let names: Vec<String> = (1..=8).map(|index| format!("account{index}")).collect();
+        let accounts: Vec<totp::migration::tests::Account> = names
+            .iter()
+            .map(|name| {
+                (
+                    &b"12345678901234567890"[..],
+                    name.as_str(),
+                    "Example",
+                    1,
+                    1,
+                    2,
+                    0,
+                )
+            })
+            .collect();
+        let data = BASE64_STANDARD.encode(totp::migration::tests::payload(&accounts));
+        let uri = format!(
+            "otpauth-migration://offline?data={}",
+            data.replace('+', "%2B")
+        );
+        assert!(uri.len() > 255);
+
+        press(&mut dashboard, KeyCode::Char('a'));
+        type_text(&mut dashboard, &uri);
+        press(&mut dashboard, KeyCode::Enter);
+
+        let saved = saved_entries(&store);
+        assert_eq!(
+            saved.active.len(),
+            names.len(),
+            "{:?}",
+            dashboard.error_banner
+        );
+        assert!(saved.active.iter().all(|entry| entry.totp.is_some()));
+    }
+
+    #[test]
+    fn imported_one_time_passwords_get_entries_without_a_password() {
+        let (mut dashboard, store, clipboard) = unlocked_dashboard();
+        press(&mut dashboard, KeyCode::Char('a'));
+        type_text(&mut dashboard, HOTP_URI);
+        press(&mut dashboard, KeyCode::Enter);
+
+        let saved = saved_entries(&store);
+        assert_eq!(labels(&saved.active), ["Example"]);
+        assert!(!saved.active[0].has_password());
+        assert_eq!(saved.active[0].strength, None);
+
+        press(&mut dashboard, KeyCode::Down);
+        press(&mut dashboard, KeyCode::Char('c'));
+        assert_eq!(clipboard.contents(), None);
+        assert!(!dashboard.password_list.items[0].is_corrupted);
+    }
+
+    #[test]
+    fn sources_that_are_not_files_are_refused_without_being_echoed() {
+        let (mut dashboard, store, _) = unlocked_dashboard();
+        let pasted_secret = "GEZDGNBVGY3TQOJQ";
+
+        press(&mut dashboard, KeyCode::Char('a'));
+        type_text(&mut dashboard, pasted_secret);
+        press(&mut dashboard, KeyCode::Enter);
+
+        let banner = dashboard.error_banner.clone().unwrap();
+        assert!(!banner.contains(pasted_secret), "{banner}");
+        assert!(saved_entries(&store).active.is_empty());
+    }
+
+    #[test]
+    fn only_key_presses_keep_the_vault_unlocked() {
+        let (mut dashboard, _, _) = unlocked_dashboard();
+        let timeout = Duration::from_secs(60);
+        dashboard.settings.lock_timeout = Some(timeout);
+
+        dashboard.last_activity = Instant::now() - timeout;
+        press(&mut dashboard, KeyCode::Down);
+        let mut state = AppState::Dashboard;
+        dashboard.handle_tick(&mut state);
+        assert!(matches!(state, AppState::Dashboard));
+
+        dashboard.last_activity = Instant::now() - timeout;
+        for event in [Event::FocusGained, Event::Resize(80, 24)] {
+            dashboard.handle_terminal_events(event, &mut state);
+        }
+        dashboard.handle_tick(&mut state);
+        assert!(matches!(state, AppState::Locked));
+    }
+
+    #[test]
+    fn copying_does_not_rewrite_the_vault() {
+        let (mut dashboard, store, clipboard) = unlocked_dashboard();
+        add_entry(&mut dashboard, "mail", "alice", "hunter2!");
+        let saved = store.borrow().load(LOGIN).unwrap();
+
+        press(&mut dashboard, KeyCode::Down);
+        press(&mut dashboard, KeyCode::Char('c'));
+
+        assert_eq!(clipboard.contents().as_deref(), Some("hunter2!"));
+        assert_eq!(store.borrow().load(LOGIN).unwrap(), saved);
+        assert!(dashboard.password_list.items[0].entry.last_used.is_some());
+    }
+
+    #[test]
+    fn entries_with_unreadable_secret_fields_are_not_edited() {
+        let (mut dashboard, store, _) = unlocked_dashboard();
+        add_entry(&mut dashboard, "mail", "alice", "hunter2!");
+        let unreadable = CustomField {
+            name: String::from("pin"),
+            value: String::from("bm90IHNlYWxlZA=="),
+            secret: true,
+        };
+        dashboard.password_list.items[0].entry.custom_fields = vec![unreadable.clone()];
+        dashboard.save_passwords();
+
+        press(&mut dashboard, KeyCode::Down);
+        press(&mut dashboard, KeyCode::Char('e'));
+        press(&mut dashboard, KeyCode::Enter);
+
+        assert!(dashboard.error_banner.is_some());
+        assert_eq!(saved_entries(&store).active[0].custom_fields, [unreadable]);
+    }
+
+    #[test]
+    fn entries_with_an_unreadable_totp_secret_are_not_edited() {
+        let (mut dashboard, store, _) = unlocked_dashboard();
+        add_entry(&mut dashboard, "mail", "alice", "hunter2!");
+        let unreadable = OneTimePassword {
+            secret: String::from("bm90IHNlYWxlZA=="),
+            parameters: totp::TotpParameters {
+                digits: 8,
+                ..Default::default()
+            },
+        };
+        dashboard.password_list.items[0].entry.totp = Some(unreadable.clone());
+        dashboard.save_passwords();
+
+        press(&mut dashboard, KeyCode::Down);
+        press(&mut dashboard, KeyCode::Char('e'));
+
+        assert!(dashboard.display_inputs.is_none());
+        assert!(dashboard.error_banner.is_some());
+        assert_eq!(saved_entries(&store).active[0].totp, Some(unreadable));
+    }
+
+    #[test]
+    fn edits_keep_the_old_password_and_untouched_secrets() {
+        let (mut dashboard, store, _) = unlocked_dashboard();
+        press(&mut dashboard, KeyCode::Char('n'));
+        type_text(&mut dashboard, "mail");
+        press(&mut dashboard, KeyCode::Down);
+        type_text(&mut dashboard, "alice");
+        press(&mut dashboard, KeyCode::Down);
+        type_text(&mut dashboard, "hunter2!");
+        // Past the URLs and tags.
+        for _ in 0..3 {
+            press(&mut dashboard, KeyCode::Down);
+        }
+        type_text(&mut dashboard, "JBSWY3DPEHPK3PXP, digits=8");
+        for _ in 0..2 {
+            press(&mut dashboard, KeyCode::Down);
+        }
+        type_text(&mut dashboard, "!pin = 1234");
+        press(&mut dashboard, KeyCode::Enter);
+        let before = saved_entries(&store).active.remove(0);
+
+        press(&mut dashboard, KeyCode::Down);
+        press(&mut dashboard, KeyCode::Char('e'));
+        type_text(&mut dashboard, " work");
+        for _ in 0..2 {
+            press(&mut dashboard, KeyCode::Down);
+        }
+        type_text(&mut dashboard, "?");
+        press(&mut dashboard, KeyCode::Enter);
+
+        let after = saved_entries(&store).active.remove(0);
+        assert_eq!(after.label, "mail work");
+        assert_ne!(after.password, before.password);
+        assert_eq!(after.history.len(), 1);
+        assert_eq!(after.history[0].password, before.password);
+        assert_eq!(after.totp, before.totp);
+        assert_eq!(after.totp.unwrap().parameters.digits, 8);
+        assert_eq!(after.custom_fields, before.custom_fields);
+    }
+
+    #[test]
+    fn the_selection_survives_locking_and_unlocking() {
+        let (mut dashboard, store, _) = unlocked_dashboard();
+        add_entry(&mut dashboard, "mail", "alice", "hunter2!");
+        add_entry(&mut dashboard, "bank", "alice", "s3cret!!");
+        press(&mut dashboard, KeyCode::Down);
+        press(&mut dashboard, KeyCode::Down);
+        assert_eq!(dashboard.password_list.selected(), Some(1));
+
+        press(&mut dashboard, KeyCode::Char('l'));
+        assert!(dashboard.password_list.items.is_empty());
+
+        let vault = vault::unlock(
+            &mut *store.borrow_mut(),
+            LOGIN,
+            SecretString::from(MASTER_PASSWORD),
+        )
+        .unwrap();
+        dashboard.handle_messages(vec![Message::Unlocked(vault)], &mut AppState::Dashboard);
+
+        assert_eq!(dashboard.password_list.selected(), Some(1));
+        assert_eq!(dashboard.password_list.items[1].entry.label, "bank");
+    }
+
+    fn labels(entries: &[Entry]) -> Vec<&str> {
+        entries.iter().map(|entry| entry.label.as_str()).collect()
+    }
+
+    #[test]
+    fn deleted_entries_can_be_brought_back_with_undo() {
+        let (mut dashboard, store, _) = unlocked_dashboard();
+        add_entry(&mut dashboard, "mail", "alice", "hunter2!");
+        add_entry(&mut dashboard, "bank", "alice", "s3cret!!");
+
+        press(&mut dashboard, KeyCode::Down);
+        press(&mut dashboard, KeyCode::Char('d'));
+        press(&mut dashboard, KeyCode::Char('y'));
+
+        let saved = saved_entries(&store);
+        assert_eq!(labels(&saved.active), ["bank"]);
+        assert_eq!(labels(&saved.trash), ["mail"]);
+
+        press(&mut dashboard, KeyCode::Char('u'));
+
+        let saved = saved_entries(&store);
+        assert_eq!(labels(&saved.active), ["mail", "bank"]);
+        assert!(saved.trash.is_empty());
+    }
+
+    #[test]
+    fn trashed_entries_can_be_restored_or_purged() {
+        let (mut dashboard, store, _) = unlocked_dashboard();
+        add_entry(&mut dashboard, "mail", "alice", "hunter2!");
+        add_entry(&mut dashboard, "bank", "alice", "s3cret!!");
+
+        for _ in 0..2 {
+            press(&mut dashboard, KeyCode::Down);
+            press(&mut dashboard, KeyCode::Char('d'));
+            press(&mut dashboard, KeyCode::Enter);
+        }
+        assert!(saved_entries(&store).active.is_empty());
+
+        press(&mut dashboard, KeyCode::Char('t'));
+        press(&mut dashboard, KeyCode::Down);
+        press(&mut dashboard, KeyCode::Char('r'));
+
+        let saved = saved_entries(&store);
+        assert_eq!(labels(&saved.active), ["mail"]);
+        assert_eq!(labels(&saved.trash), ["bank"]);
+
+        press(&mut dashboard, KeyCode::Down);
+        press(&mut dashboard, KeyCode::Char('p'));
+        press(&mut dashboard, KeyCode::Char('y'));
+
+        let saved = saved_entries(&store);
+        assert_eq!(labels(&saved.active), ["mail"]);
+        assert!(saved.trash.is_empty());
+
+        // The purged entry is gone for good, so undoing the last deletion has nothing to bring back.
+        press(&mut dashboard, KeyCode::Char('t'));
+        press(&mut dashboard, KeyCode::Char('u'));
+        assert_eq!(labels(&saved_entries(&store).active), ["mail"]);
+    }
 }
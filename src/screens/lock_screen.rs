@@ -0,0 +1,189 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::{
+    prelude::*,
+    widgets::{block::Title, Block, Paragraph},
+};
+use secrecy::SecretString;
+use symbols::border;
+
+use crate::{
+    app::{AppState, Screen},
+    backoff::Backoff,
+    components::input_field::{InputField, InputFieldState, MASTER_PASSWORD_LIMIT},
+    message_bus::{Message, MessageBus},
+    vault::{self, store::VaultStore, VaultError},
+};
+
+/// Shown while the vault of the last logged in account is locked, asking for its master
+/// password only.
+pub struct LockScreen {
+    /// The account to unlock, set by [`Message::Locked`].
+    login: Option<String>,
+    password_input: InputField,
+    message_bus: Rc<RefCell<MessageBus>>,
+    vault_store: Rc<RefCell<dyn VaultStore>>,
+
+    error_message: Option<String>,
+    /// Shared with the other screens asking for a master password.
+    backoff: Rc<RefCell<Backoff>>,
+}
+
+impl LockScreen {
+    pub fn new(
+        message_bus: Rc<RefCell<MessageBus>>,
+        vault_store: Rc<RefCell<dyn VaultStore>>,
+        backoff: Rc<RefCell<Backoff>>,
+    ) -> Self {
+        let mut password_input = InputField::default();
+        password_input.label = "Master password";
+        password_input.hide_value = true;
+        password_input.character_limit = MASTER_PASSWORD_LIMIT;
+        password_input.state = InputFieldState::Active;
+
+        LockScreen {
+            login: None,
+            password_input,
+            message_bus,
+            vault_store,
+            error_message: None,
+            backoff,
+        }
+    }
+
+    fn attempt_unlock(&mut self, state: &mut AppState) {
+        let Some(login) = self.login.clone() else {
+            return;
+        };
+        if let Some(remaining) = self.backoff.borrow().remaining() {
+            self.error_message = Some(format!(
+                "Too many failed attempts, try again in {}s",
+                remaining.as_secs() + 1
+            ));
+            return;
+        }
+
+        let password = SecretString::from(self.password_input.get_value());
+        self.password_input.clear_value();
+
+        match vault::unlock(&mut *self.vault_store.borrow_mut(), &login, password) {
+            Ok(vault) => {
+                self.message_bus
+                    .borrow_mut()
+                    .submit_message(Message::Unlocked(vault));
+
+                self.login = None;
+                self.error_message = None;
+                self.backoff.borrow_mut().reset();
+
+                *state = AppState::Dashboard
+            }
+            Err(why @ VaultError::WrongPassword) => {
+                let (failed_attempts, backoff) = self.backoff.borrow_mut().fail();
+
+                self.error_message = Some(format!(
+                    "Couldn't unlock: {why} ({failed_attempts} failed attempts), try again in {}s",
+                    backoff.as_secs()
+                ));
+            }
+            Err(why) => self.error_message = Some(format!("Couldn't unlock: {why}")),
+        }
+    }
+}
+
+impl Screen for LockScreen {
+    fn render(&mut self, frame: &mut Frame) {
+        let area = frame.area();
+        let buf = frame.buffer_mut();
+
+        let title = Title::from(" Locked ".bold());
+        let block = Block::bordered()
+            .title(title.alignment(Alignment::Center))
+            .border_set(border::THICK);
+
+        let text = Text::from(Line::from(vec![
+            "The vault of ".into(),
+            self.login.clone().unwrap_or_default().bold(),
+            " was locked. Enter its master password to continue, or press ".into(),
+            "<Esc> ".bold(),
+            "to quit".into(),
+        ]));
+
+        let layout_parts = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(3),
+                Constraint::Min(1),
+                Constraint::Length(3),
+            ])
+            .split(area);
+
+        Paragraph::new(text)
+            .block(block)
+            .alignment(Alignment::Center)
+            .render(layout_parts[0], buf);
+
+        Block::bordered()
+            .border_set(border::THICK)
+            .render(layout_parts[1], buf);
+
+        let input_area = Layout::default()
+            .direction(Direction::Vertical)
+            .flex(layout::Flex::Center)
+            .constraints([Constraint::Length(5)])
+            .split(layout_parts[1]);
+
+        let password_area = Rect::new(
+            input_area[0].x + input_area[0].width / 3,
+            input_area[0].y,
+            input_area[0].width / 3,
+            input_area[0].height,
+        );
+        self.password_input.render(password_area, buf);
+
+        if let Some(error_message) = &self.error_message {
+            Paragraph::new(error_message.as_str().red())
+                .alignment(Alignment::Center)
+                .render(layout_parts[2], buf);
+        }
+
+        if let Some(position) = self.password_input.cursor_position {
+            frame.set_cursor_position(position);
+        }
+    }
+
+    fn handle_terminal_events(&mut self, event: event::Event, state: &mut AppState) {
+        match event {
+            Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
+                match key_event.code {
+                    KeyCode::Enter => self.attempt_unlock(state),
+                    KeyCode::Esc => {
+                        self.password_input.clear_value();
+                        *state = AppState::Quit;
+                    }
+                    KeyCode::Backspace => self.password_input.remove_character(),
+                    KeyCode::Left => self.password_input.move_cursor_left(),
+                    KeyCode::Right => self.password_input.move_cursor_right(),
+                    KeyCode::Home => self.password_input.reset_cursor(),
+                    KeyCode::End => self.password_input.place_cursor_at_end(),
+                    KeyCode::Char(c) => self.password_input.add_character(c),
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_messages(&mut self, messages: Vec<Message>, _state: &mut AppState) {
+        for message in messages {
+            if let Message::Locked(login) = message {
+                self.login = Some(login);
+                self.password_input.clear_value();
+                self.error_message = None;
+            }
+        }
+    }
+
+    fn handle_tick(&mut self, _state: &mut AppState) {}
+}
@@ -1,2 +1,4 @@
+pub mod create_account;
 pub mod dashboard;
+pub mod lock_screen;
 pub mod welcome_screen;
@@ -5,12 +5,19 @@ use ratatui::{
     prelude::*,
     widgets::{block::Title, Block, Paragraph},
 };
+use secrecy::{zeroize::Zeroizing, SecretString};
 use symbols::border;
 
 use crate::{
     app::{AppState, Screen},
-    components::input_field::{InputField, InputFieldState},
+    backoff::Backoff,
+    components::{
+        input_field::{InputField, InputFieldState, MASTER_PASSWORD_LIMIT},
+        strength_meter::{StrengthMeter, METER_HEIGHT},
+    },
     message_bus::{Message, MessageBus},
+    strength,
+    vault::{self, store::VaultStore, VaultError},
 };
 
 pub struct WelcomeScreen {
@@ -18,6 +25,12 @@ pub struct WelcomeScreen {
     password_input: InputField,
     active_field: ActiveField,
     message_bus: Rc<RefCell<MessageBus>>,
+    vault_store: Rc<RefCell<dyn VaultStore>>,
+
+    error_message: Option<String>,
+    /// Shared with the other screens asking for a master password.
+    backoff: Rc<RefCell<Backoff>>,
+    can_restore_backup: bool,
 }
 
 enum ActiveField {
@@ -26,7 +39,11 @@ enum ActiveField {
 }
 
 impl WelcomeScreen {
-    pub fn new(message_bus: Rc<RefCell<MessageBus>>) -> Self {
+    pub fn new(
+        message_bus: Rc<RefCell<MessageBus>>,
+        vault_store: Rc<RefCell<dyn VaultStore>>,
+        backoff: Rc<RefCell<Backoff>>,
+    ) -> Self {
         let mut login_input = InputField::default();
         login_input.label = "Login";
         login_input.state = InputFieldState::Active;
@@ -34,12 +51,17 @@ impl WelcomeScreen {
         let mut password_input = InputField::default();
         password_input.label = "Password";
         password_input.hide_value = true;
+        password_input.character_limit = MASTER_PASSWORD_LIMIT;
 
         WelcomeScreen {
             login_input,
             password_input,
             active_field: ActiveField::Login,
             message_bus,
+            vault_store,
+            error_message: None,
+            backoff,
+            can_restore_backup: false,
         }
     }
 
@@ -48,19 +70,61 @@ impl WelcomeScreen {
             ActiveField::Login => {
                 self.focus_password();
             }
-            ActiveField::Password => {
+            ActiveField::Password => self.attempt_unlock(state, false),
+        }
+    }
+
+    /// Unlocks the vault, or with `from_backup` replaces a damaged vault with its newest
+    /// backup that opens with the entered password.
+    fn attempt_unlock(&mut self, state: &mut AppState, from_backup: bool) {
+        if let Some(remaining) = self.backoff.borrow().remaining() {
+            self.error_message = Some(format!(
+                "Too many failed attempts, try again in {}s",
+                remaining.as_secs() + 1
+            ));
+            return;
+        }
+
+        let login = self.login_input.get_value();
+        let password = SecretString::from(self.password_input.get_value());
+        let result = if from_backup {
+            vault::restore_backup(&mut *self.vault_store.borrow_mut(), &login, password)
+        } else {
+            vault::unlock(&mut *self.vault_store.borrow_mut(), &login, password)
+        };
+
+        match result {
+            Ok(vault) => {
                 self.message_bus
                     .borrow_mut()
-                    .submit_message(Message::LoginCredentials(
-                        self.login_input.get_value(),
-                        self.password_input.get_value(),
-                    ));
+                    .submit_message(Message::Unlocked(vault));
 
                 self.login_input.clear_value();
                 self.password_input.clear_value();
+                self.focus_login();
+
+                self.error_message = None;
+                self.backoff.borrow_mut().reset();
+                self.can_restore_backup = false;
 
                 *state = AppState::Dashboard
             }
+            Err(why @ (VaultError::WrongPassword | VaultError::NoUsableBackup)) => {
+                let (failed_attempts, backoff) = self.backoff.borrow_mut().fail();
+
+                self.error_message = Some(format!(
+                    "Couldn't log in: {why} ({failed_attempts} failed attempts), try again in {}s",
+                    backoff.as_secs()
+                ));
+                self.password_input.clear_value();
+            }
+            Err(why @ (VaultError::Corrupted | VaultError::Header(_))) => {
+                self.can_restore_backup = true;
+                self.error_message = Some(format!(
+                    "Couldn't log in: {why}. Press <F5> to restore the latest working backup"
+                ));
+            }
+            Err(why) => self.error_message = Some(format!("Couldn't log in: {why}")),
         }
     }
 
@@ -106,7 +170,13 @@ impl Screen for WelcomeScreen {
             .title(title.alignment(Alignment::Center))
             .border_set(border::THICK);
 
-        let text = Text::from("Log in or create an account to continue...");
+        let text = Text::from(Line::from(vec![
+            "Log in to continue, press ".into(),
+            "<Tab> ".bold(),
+            "to create a new account or ".into(),
+            "<Esc> ".bold(),
+            "to quit".into(),
+        ]));
 
         let layout_parts = Layout::default()
             .direction(Direction::Vertical)
@@ -129,7 +199,11 @@ impl Screen for WelcomeScreen {
         let input_area = Layout::default()
             .direction(Direction::Vertical)
             .flex(layout::Flex::Center)
-            .constraints([Constraint::Length(5), Constraint::Length(5)])
+            .constraints([
+                Constraint::Length(5),
+                Constraint::Length(5),
+                Constraint::Length(METER_HEIGHT),
+            ])
             .split(layout_parts[1]);
 
         let login_area = Rect::new(
@@ -148,6 +222,20 @@ impl Screen for WelcomeScreen {
         );
         self.password_input.render(password_area, buf);
 
+        let password = Zeroizing::new(self.password_input.get_value());
+        if !password.is_empty() {
+            StrengthMeter {
+                estimate: &strength::estimate(&password),
+            }
+            .render(input_area[2], buf);
+        }
+
+        if let Some(error_message) = &self.error_message {
+            Paragraph::new(error_message.as_str().red())
+                .alignment(Alignment::Center)
+                .render(layout_parts[2], buf);
+        }
+
         for input in [&self.login_input, &self.password_input].iter() {
             if let Some(position) = input.cursor_position {
                 frame.set_cursor_position(position);
@@ -172,7 +260,17 @@ impl Screen for WelcomeScreen {
                     self.handle_submit(state);
                 }
 
-                if key_event.code == KeyCode::Char('q') {
+                if key_event.code == KeyCode::F(5) && self.can_restore_backup {
+                    self.attempt_unlock(state, true);
+                }
+
+                if key_event.code == KeyCode::Tab {
+                    self.error_message = None;
+                    *state = AppState::CreateAccount;
+                }
+
+                // Every key but Esc types into the focused field, so `q` can't be taken for quitting.
+                if key_event.code == KeyCode::Esc {
                     *state = AppState::Quit;
                 }
             }
@@ -180,5 +278,107 @@ impl Screen for WelcomeScreen {
         }
     }
 
-    fn handle_messages(&mut self, message: Vec<Message>, state: &mut AppState) {}
+    fn handle_messages(&mut self, _messages: Vec<Message>, _state: &mut AppState) {}
+
+    fn handle_tick(&mut self, _state: &mut AppState) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::backend::TestBackend;
+
+    use super::*;
+    use crate::{
+        screens::{create_account::CreateAccountScreen, lock_screen::LockScreen},
+        vault::memory_store::MemoryStore,
+    };
+
+    /// Handles a key press and redraws, as the main loop does; inputs only place their cursor
+    /// when rendered.
+    fn press(screen: &mut impl Screen, code: KeyCode, state: &mut AppState) {
+        let event = Event::Key(event::KeyEvent::new(code, event::KeyModifiers::NONE));
+        screen.handle_terminal_events(event, state);
+        draw(screen);
+    }
+
+    fn draw(screen: &mut impl Screen) {
+        let mut terminal = Terminal::new(TestBackend::new(160, 50)).unwrap();
+        terminal.draw(|frame| screen.render(frame)).unwrap();
+    }
+
+    fn type_text(screen: &mut impl Screen, text: &str, state: &mut AppState) {
+        for c in text.chars() {
+            press(screen, KeyCode::Char(c), state);
+        }
+    }
+
+    #[test]
+    fn long_master_passwords_with_a_q_can_be_typed_back_in() {
+        // A seven word passphrase, longer than an input takes by default.
+        let password = "quiz-equal-quote-squeeze-liquid-unique-queen";
+        let message_bus = Rc::new(RefCell::new(MessageBus::new()));
+        let store: Rc<RefCell<dyn VaultStore>> = Rc::new(RefCell::new(MemoryStore::default()));
+
+        let mut state = AppState::CreateAccount;
+        let mut create_account = CreateAccountScreen::new(message_bus.clone(), store.clone());
+        draw(&mut create_account);
+        type_text(&mut create_account, "quentin", &mut state);
+        for _ in 0..2 {
+            press(&mut create_account, KeyCode::Enter, &mut state);
+            type_text(&mut create_account, password, &mut state);
+        }
+        press(&mut create_account, KeyCode::Enter, &mut state);
+        assert!(matches!(state, AppState::Dashboard));
+
+        let mut state = AppState::WelcomeScreen;
+        let mut welcome = WelcomeScreen::new(message_bus.clone(), store, Rc::default());
+        draw(&mut welcome);
+        type_text(&mut welcome, "quentin", &mut state);
+        press(&mut welcome, KeyCode::Enter, &mut state);
+        type_text(&mut welcome, password, &mut state);
+        press(&mut welcome, KeyCode::Enter, &mut state);
+
+        assert!(
+            matches!(state, AppState::Dashboard),
+            "{:?}",
+            welcome.error_message
+        );
+        let unlocked = message_bus.borrow_mut().poll_messages();
+        assert!(
+            matches!(unlocked.as_slice(), [_, Message::Unlocked(vault)] if vault.login() == "quentin")
+        );
+    }
+
+    #[test]
+    fn failed_attempts_on_the_login_screen_hold_back_the_lock_screen() {
+        let message_bus = Rc::new(RefCell::new(MessageBus::new()));
+        let store: Rc<RefCell<dyn VaultStore>> = Rc::new(RefCell::new(MemoryStore::default()));
+        vault::create(
+            &mut *store.borrow_mut(),
+            "alice",
+            SecretString::from("correct horse"),
+        )
+        .unwrap();
+        let backoff = Rc::new(RefCell::new(Backoff::default()));
+
+        let mut state = AppState::WelcomeScreen;
+        let mut welcome =
+            WelcomeScreen::new(message_bus.clone(), store.clone(), Rc::clone(&backoff));
+        draw(&mut welcome);
+        type_text(&mut welcome, "alice", &mut state);
+        press(&mut welcome, KeyCode::Enter, &mut state);
+        type_text(&mut welcome, "wrong horse", &mut state);
+        press(&mut welcome, KeyCode::Enter, &mut state);
+        assert!(matches!(state, AppState::WelcomeScreen));
+
+        let mut state = AppState::Locked;
+        let mut lock_screen = LockScreen::new(message_bus.clone(), store, backoff);
+        lock_screen.handle_messages(vec![Message::Locked(String::from("alice"))], &mut state);
+        draw(&mut lock_screen);
+        type_text(&mut lock_screen, "correct horse", &mut state);
+        press(&mut lock_screen, KeyCode::Enter, &mut state);
+
+        assert!(matches!(state, AppState::Locked));
+        assert!(message_bus.borrow_mut().poll_messages().is_empty());
+    }
 }
@@ -0,0 +1,32 @@
+use std::time::Duration;
+
+/// Previous passwords kept per entry unless configured otherwise.
+pub const DEFAULT_HISTORY_DEPTH: usize = 10;
+
+/// How long a revealed password stays readable unless configured otherwise.
+pub const DEFAULT_REVEAL_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long the vault stays unlocked without any input unless configured otherwise.
+pub const DEFAULT_LOCK_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+/// Preferences given on the command line that shape how the screens behave.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Settings {
+    /// How many replaced passwords each entry keeps, `0` keeps none.
+    pub history_depth: usize,
+    /// `None` keeps a revealed password shown until it is masked by hand, set with
+    /// `--reveal-timeout 0`.
+    pub reveal_timeout: Option<Duration>,
+    /// `None` keeps the vault unlocked until it is locked by hand, set with `--lock-timeout 0`.
+    pub lock_timeout: Option<Duration>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            history_depth: DEFAULT_HISTORY_DEPTH,
+            reveal_timeout: Some(DEFAULT_REVEAL_TIMEOUT),
+            lock_timeout: Some(DEFAULT_LOCK_TIMEOUT),
+        }
+    }
+}
@@ -0,0 +1,439 @@
+use std::{collections::HashMap, sync::OnceLock};
+
+use crate::generator::passphrase;
+
+/// Passwords seen most often in public leaks, the most common first.
+const COMMON_PASSWORDS: &str = include_str!("common_passwords.txt");
+
+/// Rows of a US keyboard, unshifted and shifted, walked along by keyboard patterns.
+const KEYBOARD_ROWS: &[&str] = &[
+    "`1234567890-=",
+    "qwertyuiop[]\\",
+    "asdfghjkl;'",
+    "zxcvbnm,./",
+    "~!@#$%^&*()_+",
+    "QWERTYUIOP{}|",
+    "ASDFGHJKL:\"",
+    "ZXCVBNM<>?",
+];
+
+/// Look-alike characters undone before looking words up, e.g. `p@ssw0rd`.
+const L33T: &[(char, char)] = &[
+    ('4', 'a'),
+    ('@', 'a'),
+    ('8', 'b'),
+    ('(', 'c'),
+    ('3', 'e'),
+    ('6', 'g'),
+    ('1', 'i'),
+    ('!', 'i'),
+    ('|', 'l'),
+    ('0', 'o'),
+    ('$', 's'),
+    ('5', 's'),
+    ('7', 't'),
+    ('+', 't'),
+    ('2', 'z'),
+];
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Pattern {
+    /// A listed password or word, possibly reversed or with look-alike substitutions.
+    Dictionary {
+        common: bool,
+        reversed: bool,
+        l33t: bool,
+    },
+    /// Characters following each other in the alphabet or on the number line, like `abcd`.
+    Sequence,
+    /// Neighbouring keys on a keyboard row, like `asdf`.
+    Keyboard,
+    /// The same character or block over and over, like `aaaa` or `abcabc`.
+    Repeat,
+    /// A year or a full date, like `1987` or `12.03.1987`.
+    Date,
+}
+
+/// A part of the password that an attacker would try before brute force.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Match {
+    pub start: usize,
+    /// Exclusive.
+    pub end: usize,
+    pub pattern: Pattern,
+    /// Base-10 logarithm of how many guesses this part takes on its own.
+    pub guesses_log10: f64,
+}
+
+/// Words mapped to how many guesses it takes to reach them.
+type Dictionary = HashMap<&'static str, usize>;
+
+/// The common passwords and the passphrase words.
+fn dictionaries() -> &'static (Dictionary, Dictionary) {
+    static DICTIONARIES: OnceLock<(Dictionary, Dictionary)> = OnceLock::new();
+
+    DICTIONARIES.get_or_init(|| {
+        let word_count = passphrase::wordlist().count();
+        let ranked = |words: Vec<&'static str>| {
+            words
+                .into_iter()
+                .enumerate()
+                .map(|(index, word)| (word, index + 1))
+                .collect()
+        };
+
+        (
+            ranked(COMMON_PASSWORDS.lines().collect()),
+            // The wordlist is sorted alphabetically, so every word is equally likely.
+            passphrase::wordlist()
+                .map(|word| (word, word_count))
+                .collect(),
+        )
+    })
+}
+
+/// Every pattern found anywhere in `password`, overlapping ones included.
+pub fn find_matches(password: &[char]) -> Vec<Match> {
+    let mut matches = vec![];
+
+    matches.extend(dictionary_matches(password));
+    matches.extend(sequence_matches(password));
+    matches.extend(keyboard_matches(password));
+    matches.extend(repeat_matches(password));
+    matches.extend(date_matches(password));
+
+    matches
+}
+
+fn dictionary_matches(password: &[char]) -> Vec<Match> {
+    let (common, words) = dictionaries();
+    let lowercase: Vec<char> = password.iter().flat_map(|c| c.to_lowercase()).collect();
+    if lowercase.len() != password.len() {
+        return vec![];
+    }
+    let unleeted: Vec<char> = lowercase.iter().map(|c| unleet(*c)).collect();
+
+    let mut matches = vec![];
+    for start in 0..password.len() {
+        for end in start + 3..=password.len().min(start + 32) {
+            let original = &password[start..end];
+
+            for (candidate, l33t) in [
+                (&lowercase[start..end], false),
+                (&unleeted[start..end], true),
+            ] {
+                if l33t && candidate == &lowercase[start..end] {
+                    continue;
+                }
+
+                for reversed in [false, true] {
+                    let word: String = if reversed {
+                        candidate.iter().rev().collect()
+                    } else {
+                        candidate.iter().collect()
+                    };
+
+                    let found = common
+                        .get(word.as_str())
+                        .map(|rank| (true, *rank))
+                        .or_else(|| words.get(word.as_str()).map(|rank| (false, *rank)));
+                    let Some((is_common, rank)) = found else {
+                        continue;
+                    };
+
+                    let mut guesses_log10 = (rank as f64).log10() + uppercase_variations(original);
+                    if l33t {
+                        guesses_log10 += l33t_variations(original);
+                    }
+                    if reversed {
+                        guesses_log10 += 2f64.log10();
+                    }
+
+                    matches.push(Match {
+                        start,
+                        end,
+                        pattern: Pattern::Dictionary {
+                            common: is_common,
+                            reversed,
+                            l33t,
+                        },
+                        guesses_log10,
+                    });
+                }
+            }
+        }
+    }
+
+    matches
+}
+
+fn unleet(c: char) -> char {
+    L33T.iter()
+        .find(|(leet, _)| *leet == c)
+        .map_or(c, |(_, letter)| *letter)
+}
+
+/// How many ways the letters of a word may have been capitalized, the usual ones (all
+/// lowercase, first letter, all uppercase) being tried first.
+fn uppercase_variations(word: &[char]) -> f64 {
+    let upper = word.iter().filter(|c| c.is_uppercase()).count();
+    let lower = word.iter().filter(|c| c.is_lowercase()).count();
+
+    if upper == 0 {
+        0.0
+    } else if lower == 0 || (upper == 1 && word[0].is_uppercase()) {
+        2f64.log10()
+    } else {
+        let possibilities: f64 = (1..=upper.min(lower))
+            .map(|chosen| binomial(upper + lower, chosen))
+            .sum();
+        possibilities.log10()
+    }
+}
+
+fn l33t_variations(word: &[char]) -> f64 {
+    let substituted = word
+        .iter()
+        .filter(|c| L33T.iter().any(|(leet, _)| leet == *c))
+        .count();
+
+    (substituted as f64 + 1.0).log10() + 2f64.log10()
+}
+
+fn binomial(n: usize, k: usize) -> f64 {
+    (0..k).fold(1.0, |result, i| result * (n - i) as f64 / (i + 1) as f64)
+}
+
+fn sequence_matches(password: &[char]) -> Vec<Match> {
+    runs(password, 3, |previous, next| {
+        let delta = next as i64 - previous as i64;
+        delta.abs() == 1 && class(previous) == class(next) && class(next) != CharClass::Other
+    })
+    .into_iter()
+    .map(|(start, end)| {
+        let first = password[start];
+        let base: f64 = if "aAzZ019".contains(first) {
+            4.0
+        } else if first.is_ascii_digit() {
+            10.0
+        } else {
+            26.0
+        };
+        let descending = (password[start + 1] as i64) < (first as i64);
+
+        Match {
+            start,
+            end,
+            pattern: Pattern::Sequence,
+            guesses_log10: (base * (end - start) as f64 * if descending { 2.0 } else { 1.0 })
+                .log10(),
+        }
+    })
+    .collect()
+}
+
+fn keyboard_matches(password: &[char]) -> Vec<Match> {
+    let adjacent = |previous: char, next: char| {
+        KEYBOARD_ROWS.iter().any(|row| {
+            let row: Vec<char> = row.chars().collect();
+            row.windows(2).any(|pair| {
+                (pair[0] == previous && pair[1] == next) || (pair[1] == previous && pair[0] == next)
+            })
+        })
+    };
+
+    runs(password, 4, adjacent)
+        .into_iter()
+        // Digits in a row are better described as a sequence.
+        .filter(|(start, end)| !password[*start..*end].iter().all(char::is_ascii_digit))
+        .map(|(start, end)| Match {
+            start,
+            end,
+            pattern: Pattern::Keyboard,
+            // Any of ~47 starting keys, walking left or right, for this many keys.
+            guesses_log10: (47.0 * 2.0 * (end - start) as f64).log10(),
+        })
+        .collect()
+}
+
+fn repeat_matches(password: &[char]) -> Vec<Match> {
+    let mut matches = vec![];
+    // Blocks recur at every offset of a repeat, and each takes a full estimate of its own.
+    let mut block_guesses: HashMap<&[char], f64> = HashMap::new();
+
+    for start in 0..password.len() {
+        for period in 1..=(password.len() - start) / 2 {
+            let mut end = start + period;
+            while end + period <= password.len()
+                && password[end..end + period] == password[start..start + period]
+            {
+                end += period;
+            }
+
+            let count = (end - start) / period;
+            if count < 2 || (period == 1 && count < 3) {
+                continue;
+            }
+
+            // `abab` repeated is `ab` repeated twice as often, which is found with the shorter
+            // period already.
+            let block = &password[start..start + period];
+            if is_repetition(block) {
+                continue;
+            }
+
+            let guesses = *block_guesses
+                .entry(block)
+                .or_insert_with(|| super::minimum_guesses_log10(block));
+            matches.push(Match {
+                start,
+                end,
+                pattern: Pattern::Repeat,
+                guesses_log10: guesses + (count as f64).log10(),
+            });
+        }
+    }
+
+    matches
+}
+
+/// Whether `block` is a shorter block repeated, like `abab`.
+fn is_repetition(block: &[char]) -> bool {
+    (1..block.len())
+        .filter(|period| block.len().is_multiple_of(*period))
+        .any(|period| block.chunks(period).all(|chunk| chunk == &block[..period]))
+}
+
+fn date_matches(password: &[char]) -> Vec<Match> {
+    let mut matches = vec![];
+
+    for start in 0..password.len() {
+        for end in start + 4..=password.len().min(start + 10) {
+            let candidate = &password[start..end];
+
+            let guesses: f64 = if end - start == 4 && is_year(candidate) {
+                // Recent years are tried first.
+                50.0
+            } else if is_date(candidate) {
+                // Any day of a century, in any of a few orders.
+                365.0 * 100.0 * 3.0
+            } else {
+                continue;
+            };
+
+            matches.push(Match {
+                start,
+                end,
+                pattern: Pattern::Date,
+                guesses_log10: guesses.log10(),
+            });
+        }
+    }
+
+    matches
+}
+
+fn is_year(digits: &[char]) -> bool {
+    let year: String = digits.iter().collect();
+    year.parse::<u32>()
+        .is_ok_and(|year| (1900..=2099).contains(&year))
+}
+
+/// Whether `candidate` reads as day, month and year in some order, with or without
+/// separators, e.g. `120387`, `1987-03-12` or `3.12.87`.
+fn is_date(candidate: &[char]) -> bool {
+    let text: String = candidate.iter().collect();
+    let parts: Vec<&str> = text.split(['-', '/', '.', ' ', '_']).collect();
+
+    let numbers: Vec<Vec<u32>> = match parts.as_slice() {
+        [whole] if whole.len() == 6 || whole.len() == 8 => {
+            if !whole.chars().all(|c| c.is_ascii_digit()) {
+                return false;
+            }
+
+            // Every way of cutting the digits into three plausible parts.
+            let mut splits = vec![];
+            for first in 1..=4.min(whole.len() - 2) {
+                for second in first + 1..=(first + 2).min(whole.len() - 1) {
+                    splits.push(vec![
+                        whole[..first].parse().unwrap(),
+                        whole[first..second].parse().unwrap(),
+                        whole[second..].parse().unwrap(),
+                    ]);
+                }
+            }
+            splits
+        }
+        [_, _, _] => {
+            let numbers: Option<Vec<u32>> = parts
+                .iter()
+                .map(|part| {
+                    (!part.is_empty() && part.len() <= 4)
+                        .then(|| part.parse().ok())
+                        .flatten()
+                })
+                .collect();
+            match numbers {
+                Some(numbers) => vec![numbers],
+                None => return false,
+            }
+        }
+        _ => return false,
+    };
+
+    numbers.iter().any(|numbers| {
+        let plausible = |day: u32, month: u32, year: u32| {
+            (1..=31).contains(&day)
+                && (1..=12).contains(&month)
+                && (year <= 99 || (1900..=2099).contains(&year))
+        };
+
+        match numbers.as_slice() {
+            [a, b, c] => plausible(*a, *b, *c) || plausible(*b, *a, *c) || plausible(*c, *b, *a),
+            _ => false,
+        }
+    })
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum CharClass {
+    Lower,
+    Upper,
+    Digit,
+    Other,
+}
+
+fn class(c: char) -> CharClass {
+    if c.is_ascii_lowercase() {
+        CharClass::Lower
+    } else if c.is_ascii_uppercase() {
+        CharClass::Upper
+    } else if c.is_ascii_digit() {
+        CharClass::Digit
+    } else {
+        CharClass::Other
+    }
+}
+
+/// Maximal stretches of at least `min_length` characters in which every character
+/// `follows` the one before it, as `(start, end)` pairs.
+fn runs(
+    password: &[char],
+    min_length: usize,
+    follows: impl Fn(char, char) -> bool,
+) -> Vec<(usize, usize)> {
+    let mut runs = vec![];
+    let mut start = 0;
+
+    for end in 1..=password.len() {
+        let continues = end < password.len() && follows(password[end - 1], password[end]);
+        if !continues {
+            if end - start >= min_length {
+                runs.push((start, end));
+            }
+            start = end;
+        }
+    }
+
+    runs
+}
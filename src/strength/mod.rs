@@ -0,0 +1,337 @@
+mod matching;
+
+use matching::{Match, Pattern};
+
+/// Guesses per second of an offline attack on a slow hash such as Argon2 or bcrypt.
+const GUESSES_PER_SECOND: f64 = 1e4;
+
+/// How hard a password is to guess, in the spirit of zxcvbn: the password is cut into the
+/// patterns an attacker tries first (common passwords, words, sequences, keyboard rows,
+/// repeats and dates) and whatever is left is brute forced.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Estimate {
+    /// From 0, guessed almost at once, to 4, out of reach of an offline attack.
+    pub score: u8,
+    pub guesses_log10: f64,
+    pub crack_seconds: f64,
+    /// What makes the password weak, if anything stands out.
+    pub warning: Option<&'static str>,
+    pub suggestions: Vec<&'static str>,
+}
+
+pub const MAX_SCORE: u8 = 4;
+
+/// Characters looked at, as in zxcvbn. Matching takes time that grows much faster than the
+/// length, and the estimate runs on every frame of a screen with a strength meter.
+const MAX_MATCHED_LENGTH: usize = 100;
+
+/// Estimates how hard `password` is to guess, from its first [`MAX_MATCHED_LENGTH`]
+/// characters.
+pub fn estimate(password: &str) -> Estimate {
+    let chars: Vec<char> = password.chars().take(MAX_MATCHED_LENGTH).collect();
+    let (guesses_log10, sequence) = most_guessable(&chars);
+
+    let score = match guesses_log10 {
+        g if g < 3.0 => 0,
+        g if g < 6.0 => 1,
+        g if g < 8.0 => 2,
+        g if g < 10.0 => 3,
+        _ => 4,
+    };
+    let (warning, suggestions) = feedback(&chars, score, &sequence);
+
+    Estimate {
+        score,
+        guesses_log10,
+        crack_seconds: 10f64.powf(guesses_log10) / GUESSES_PER_SECOND,
+        warning,
+        suggestions,
+    }
+}
+
+impl Estimate {
+    pub fn label(&self) -> &'static str {
+        score_label(self.score)
+    }
+
+    /// The crack time in the largest unit that fits, e.g. `3 hours` or `centuries`.
+    pub fn crack_time(&self) -> String {
+        let seconds = self.crack_seconds;
+        let units = [
+            (60.0, "second"),
+            (60.0 * 60.0, "minute"),
+            (60.0 * 60.0 * 24.0, "hour"),
+            (60.0 * 60.0 * 24.0 * 31.0, "day"),
+            (60.0 * 60.0 * 24.0 * 365.0, "month"),
+            (60.0 * 60.0 * 24.0 * 365.0 * 100.0, "year"),
+        ];
+
+        if seconds < 1.0 {
+            return String::from("less than a second");
+        }
+
+        let mut unit_seconds = 1.0;
+        for (limit, name) in units {
+            if seconds < limit {
+                let count = (seconds / unit_seconds).round() as u64;
+                let plural = if count == 1 { "" } else { "s" };
+                return format!("{count} {name}{plural}");
+            }
+            unit_seconds = match name {
+                "second" => 60.0,
+                "minute" => 60.0 * 60.0,
+                "hour" => 60.0 * 60.0 * 24.0,
+                "day" => 60.0 * 60.0 * 24.0 * 31.0,
+                _ => 60.0 * 60.0 * 24.0 * 365.0,
+            };
+        }
+
+        String::from("centuries")
+    }
+}
+
+pub fn score_label(score: u8) -> &'static str {
+    match score {
+        0 => "very weak",
+        1 => "weak",
+        2 => "fair",
+        3 => "strong",
+        _ => "very strong",
+    }
+}
+
+/// Base-10 logarithm of the guesses needed for `password` on its own, used for the blocks
+/// of repeats.
+fn minimum_guesses_log10(password: &[char]) -> f64 {
+    most_guessable(password).0
+}
+
+/// The cheapest way to guess `password` as a sequence of matches and brute-forced
+/// stretches, with its guesses as a base-10 logarithm.
+fn most_guessable(password: &[char]) -> (f64, Vec<Match>) {
+    let length = password.len();
+    if length == 0 {
+        return (0.0, vec![]);
+    }
+
+    let matches = matching::find_matches(password);
+    let brute_force_log10 = brute_force_cardinality(password).log10();
+
+    // best[end] holds the guesses for password[..end], the number of parts used and how
+    // the last part was guessed: `None` for a brute-forced character.
+    let mut best: Vec<(f64, usize, Option<usize>)> = vec![(f64::INFINITY, 0, None); length + 1];
+    best[0] = (0.0, 0, None);
+
+    for end in 1..=length {
+        let (previous, parts, last) = best[end - 1];
+        let continues_brute_force = end > 1 && last.is_none();
+        let candidate = (
+            previous + brute_force_log10,
+            if continues_brute_force {
+                parts
+            } else {
+                parts + 1
+            },
+            None,
+        );
+        best[end] = candidate;
+
+        for (index, found) in matches.iter().enumerate().filter(|(_, m)| m.end == end) {
+            let (previous, parts, _) = best[found.start];
+            let guesses = previous + found.guesses_log10;
+            if with_order(guesses, parts + 1) < with_order(best[end].0, best[end].1) {
+                best[end] = (guesses, parts + 1, Some(index));
+            }
+        }
+    }
+
+    let mut sequence = vec![];
+    let mut end = length;
+    while end > 0 {
+        match best[end].2 {
+            Some(index) => {
+                sequence.push(matches[index].clone());
+                end = matches[index].start;
+            }
+            None => end -= 1,
+        }
+    }
+    sequence.reverse();
+
+    let (guesses, parts, _) = best[length];
+    (with_order(guesses, parts), sequence)
+}
+
+/// Adds the `parts!` ways the parts could have been put together.
+fn with_order(guesses_log10: f64, parts: usize) -> f64 {
+    guesses_log10 + (1..=parts).map(|part| (part as f64).log10()).sum::<f64>()
+}
+
+fn brute_force_cardinality(password: &[char]) -> f64 {
+    let mut cardinality = 0.0;
+
+    if password.iter().any(char::is_ascii_lowercase) {
+        cardinality += 26.0;
+    }
+    if password.iter().any(char::is_ascii_uppercase) {
+        cardinality += 26.0;
+    }
+    if password.iter().any(char::is_ascii_digit) {
+        cardinality += 10.0;
+    }
+    if password.iter().any(char::is_ascii_punctuation) || password.contains(&' ') {
+        cardinality += 33.0;
+    }
+    if password.iter().any(|c| !c.is_ascii()) {
+        cardinality += 100.0;
+    }
+
+    f64::max(cardinality, 10.0)
+}
+
+fn feedback(
+    password: &[char],
+    score: u8,
+    sequence: &[Match],
+) -> (Option<&'static str>, Vec<&'static str>) {
+    if password.is_empty() {
+        return (
+            None,
+            vec![
+                "Use a few words, avoid common phrases",
+                "No need for symbols, digits, or uppercase letters",
+            ],
+        );
+    }
+    if score > 2 {
+        return (None, vec![]);
+    }
+
+    let mut suggestions = vec!["Add another word or two. Uncommon words are better."];
+    let Some(longest) = sequence.iter().max_by_key(|found| found.end - found.start) else {
+        return (None, suggestions);
+    };
+
+    let warning = match &longest.pattern {
+        Pattern::Dictionary {
+            common,
+            reversed,
+            l33t,
+        } => {
+            let word = &password[longest.start..longest.end];
+            if word.iter().skip(1).any(|c| c.is_uppercase()) {
+                suggestions.push("All-uppercase is almost as easy to guess as all-lowercase");
+            } else if word[0].is_uppercase() {
+                suggestions.push("Capitalization doesn't help very much");
+            }
+            if *reversed {
+                suggestions.push("Reversed words aren't much harder to guess");
+            }
+            if *l33t {
+                suggestions
+                    .push("Predictable substitutions like '@' instead of 'a' don't help very much");
+            }
+
+            if *common && sequence.len() == 1 {
+                "This is a very common password"
+            } else if *common {
+                "This is similar to a commonly used password"
+            } else if sequence.len() == 1 {
+                "A word by itself is easy to guess"
+            } else {
+                "Common words are easy to guess"
+            }
+        }
+        Pattern::Sequence => {
+            suggestions.push("Avoid sequences");
+            "Sequences like abc or 6543 are easy to guess"
+        }
+        Pattern::Keyboard => {
+            suggestions.push("Use a longer keyboard pattern with more turns");
+            "Straight rows of keys are easy to guess"
+        }
+        Pattern::Repeat => {
+            suggestions.push("Avoid repeated words and characters");
+            "Repeats like \"abcabcabc\" are only slightly harder to guess than \"abc\""
+        }
+        Pattern::Date => {
+            suggestions.push("Avoid dates and years that are associated with you");
+            "Dates are often easy to guess"
+        }
+    };
+
+    (Some(warning), suggestions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn common_and_patterned_passwords_score_low() {
+        for password in [
+            "1234",
+            "password",
+            "P@ssw0rd",
+            "qwerty",
+            "abcdefgh",
+            "aaaaaaaa",
+            "12.03.1987",
+            "drowssap",
+            "monkey1",
+        ] {
+            let estimate = estimate(password);
+
+            assert!(estimate.score <= 1, "{password}: {estimate:?}");
+            assert!(estimate.warning.is_some(), "{password}: {estimate:?}");
+        }
+    }
+
+    #[test]
+    fn long_random_passwords_score_high() {
+        for password in [
+            "correct-horse-battery-staple-Ql7",
+            "k#8Vq!2zR@p9&Lw",
+            "x7Tq9mZr4Kp2Wb",
+        ] {
+            let estimate = estimate(password);
+
+            assert_eq!(estimate.score, MAX_SCORE, "{password}: {estimate:?}");
+            assert_eq!(estimate.warning, None);
+        }
+    }
+
+    #[test]
+    fn long_repetitive_passwords_are_estimated_quickly() {
+        let started = std::time::Instant::now();
+
+        for password in ["a".repeat(128), "ab".repeat(64), "abcdefgh".repeat(16)] {
+            let estimate = estimate(&password);
+            assert!(estimate.score <= 1, "{password}: {estimate:?}");
+        }
+        estimate(&"abaabaabbaba".repeat(11));
+
+        // Several seconds per password before blocks were cached, even in release builds.
+        assert!(
+            started.elapsed().as_secs_f64() < 2.0,
+            "{:?}",
+            started.elapsed()
+        );
+    }
+
+    #[test]
+    fn crack_times_read_naturally() {
+        let mut estimate = estimate("");
+
+        for (seconds, expected) in [
+            (0.5, "less than a second"),
+            (1.0, "1 second"),
+            (90.0, "2 minutes"),
+            (3.0 * 3600.0, "3 hours"),
+            (1e12, "centuries"),
+        ] {
+            estimate.crack_seconds = seconds;
+            assert_eq!(estimate.crack_time(), expected);
+        }
+    }
+}
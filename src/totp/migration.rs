@@ -0,0 +1,241 @@
+//! Decoding of the `MigrationPayload` protobuf message behind `otpauth-migration://` URIs,
+//! which Google Authenticator and compatible apps use to export accounts:
+//!
+//! ```text
+//! message MigrationPayload {
+//!   message OtpParameters {
+//!     bytes secret = 1;
+//!     string name = 2;
+//!     string issuer = 3;
+//!     Algorithm algorithm = 4;  // 1 SHA1, 2 SHA256, 3 SHA512, 4 MD5
+//!     DigitCount digits = 5;    // 1 six, 2 eight
+//!     OtpType type = 6;         // 1 HOTP, 2 TOTP
+//!     int64 counter = 7;
+//!   }
+//!   repeated OtpParameters otp_parameters = 1;
+//!   int32 version = 2;
+//!   int32 batch_size = 3;
+//!   int32 batch_index = 4;
+//!   int32 batch_id = 5;
+//! }
+//! ```
+
+use super::Algorithm;
+
+const VARINT: u64 = 0;
+const FIXED64: u64 = 1;
+const LENGTH_DELIMITED: u64 = 2;
+const FIXED32: u64 = 5;
+
+/// One account of a migration payload. Unspecified enums are left as `None`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ExportedOtp {
+    pub secret: Vec<u8>,
+    pub name: String,
+    pub issuer: String,
+    pub algorithm: Option<Algorithm>,
+    pub digits: Option<u32>,
+    pub hotp: bool,
+    pub counter: u64,
+}
+
+/// The accounts of a serialized `MigrationPayload`, or `None` if it is malformed or uses
+/// an algorithm other than SHA-1, SHA-256 or SHA-512.
+pub fn decode(payload: &[u8]) -> Option<Vec<ExportedOtp>> {
+    let mut accounts = vec![];
+
+    for field in Fields::new(payload) {
+        if let (1, Value::Bytes(message)) = field? {
+            accounts.push(decode_parameters(message)?);
+        }
+    }
+
+    Some(accounts)
+}
+
+fn decode_parameters(message: &[u8]) -> Option<ExportedOtp> {
+    let mut otp = ExportedOtp::default();
+
+    for field in Fields::new(message) {
+        match field? {
+            (1, Value::Bytes(secret)) => otp.secret = secret.to_vec(),
+            (2, Value::Bytes(name)) => otp.name = String::from_utf8(name.to_vec()).ok()?,
+            (3, Value::Bytes(issuer)) => otp.issuer = String::from_utf8(issuer.to_vec()).ok()?,
+            (4, Value::Varint(algorithm)) => {
+                otp.algorithm = match algorithm {
+                    0 => None,
+                    1 => Some(Algorithm::Sha1),
+                    2 => Some(Algorithm::Sha256),
+                    3 => Some(Algorithm::Sha512),
+                    _ => return None,
+                }
+            }
+            (5, Value::Varint(digits)) => {
+                otp.digits = match digits {
+                    1 => Some(6),
+                    2 => Some(8),
+                    _ => None,
+                }
+            }
+            (6, Value::Varint(kind)) => otp.hotp = kind == 1,
+            (7, Value::Varint(counter)) => otp.counter = counter,
+            // Unknown fields are skipped, as protobuf readers do.
+            _ => {}
+        }
+    }
+
+    Some(otp)
+}
+
+enum Value<'a> {
+    Varint(u64),
+    Bytes(&'a [u8]),
+    /// Fixed width values, which the payload does not use.
+    Fixed,
+}
+
+/// The `(field number, value)` pairs of a protobuf message, `None` once it turns out to be
+/// malformed.
+struct Fields<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Fields<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Fields { bytes }
+    }
+
+    fn varint(&mut self) -> Option<u64> {
+        let mut value: u64 = 0;
+
+        for shift in (0..64).step_by(7) {
+            let (byte, rest) = self.bytes.split_first()?;
+            self.bytes = rest;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Some(value);
+            }
+        }
+
+        None
+    }
+
+    fn take(&mut self, length: usize) -> Option<&'a [u8]> {
+        if length > self.bytes.len() {
+            return None;
+        }
+        let (taken, rest) = self.bytes.split_at(length);
+        self.bytes = rest;
+
+        Some(taken)
+    }
+
+    fn field(&mut self) -> Option<(u64, Value<'a>)> {
+        let key = self.varint()?;
+        let value = match key & 7 {
+            VARINT => Value::Varint(self.varint()?),
+            LENGTH_DELIMITED => {
+                let length = usize::try_from(self.varint()?).ok()?;
+                Value::Bytes(self.take(length)?)
+            }
+            FIXED64 => self.take(8).map(|_| Value::Fixed)?,
+            FIXED32 => self.take(4).map(|_| Value::Fixed)?,
+            _ => return None,
+        };
+
+        Some((key >> 3, value))
+    }
+}
+
+impl<'a> Iterator for Fields<'a> {
+    type Item = Option<(u64, Value<'a>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.bytes.is_empty() {
+            return None;
+        }
+
+        let field = self.field();
+        if field.is_none() {
+            // Nothing sensible follows a malformed field.
+            self.bytes = &[];
+        }
+
+        Some(field)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    fn varint(mut value: u64, out: &mut Vec<u8>) {
+        while value >= 0x80 {
+            out.push(value as u8 | 0x80);
+            value >>= 7;
+        }
+        out.push(value as u8);
+    }
+
+    fn bytes(number: u64, value: &[u8], out: &mut Vec<u8>) {
+        varint(number << 3 | LENGTH_DELIMITED, out);
+        varint(value.len() as u64, out);
+        out.extend_from_slice(value);
+    }
+
+    fn number(number: u64, value: u64, out: &mut Vec<u8>) {
+        varint(number << 3 | VARINT, out);
+        varint(value, out);
+    }
+
+    /// `(secret, name, issuer, algorithm, digits, type, counter)`, with the enums as their
+    /// protobuf numbers.
+    pub type Account<'a> = (&'a [u8], &'a str, &'a str, u64, u64, u64, u64);
+
+    /// A payload of `accounts`.
+    pub fn payload(accounts: &[Account]) -> Vec<u8> {
+        let mut payload = vec![];
+
+        for (secret, name, issuer, algorithm, digits, kind, counter) in accounts {
+            let mut message = vec![];
+            bytes(1, secret, &mut message);
+            bytes(2, name.as_bytes(), &mut message);
+            bytes(3, issuer.as_bytes(), &mut message);
+            number(4, *algorithm, &mut message);
+            number(5, *digits, &mut message);
+            number(6, *kind, &mut message);
+            number(7, *counter, &mut message);
+
+            bytes(1, &message, &mut payload);
+        }
+        number(2, 1, &mut payload);
+        number(3, 1, &mut payload);
+
+        payload
+    }
+
+    #[test]
+    fn payloads_decode_and_reject_damage() {
+        let payload = payload(&[(b"secret", "alice", "Example", 3, 2, 1, 300)]);
+
+        assert_eq!(
+            decode(&payload),
+            Some(vec![ExportedOtp {
+                secret: b"secret".to_vec(),
+                name: String::from("alice"),
+                issuer: String::from("Example"),
+                algorithm: Some(Algorithm::Sha512),
+                digits: Some(8),
+                hotp: true,
+                counter: 300,
+            }])
+        );
+
+        assert_eq!(decode(&payload[..payload.len() / 2]), None);
+        assert_eq!(decode(&[0x0a, 0x05, 0x01]), None);
+        assert_eq!(
+            decode(&self::payload(&[(b"x", "md5", "", 4, 1, 2, 0)])),
+            None
+        );
+    }
+}
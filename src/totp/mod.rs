@@ -0,0 +1,353 @@
+pub mod migration;
+pub mod otpauth;
+
+use std::fmt;
+
+use hmac::{digest::KeyInit, Hmac, Mac};
+use sha1::Sha1;
+use sha2::{Sha256, Sha512};
+
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+pub const DEFAULT_DIGITS: u32 = 6;
+pub const DEFAULT_PERIOD: u64 = 30;
+
+/// The hash behind the HMAC, SHA-1 for nearly every service.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Algorithm {
+    #[default]
+    Sha1,
+    Sha256,
+    Sha512,
+}
+
+impl Algorithm {
+    pub const ALL: [Algorithm; 3] = [Algorithm::Sha1, Algorithm::Sha256, Algorithm::Sha512];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Algorithm::Sha1 => "SHA1",
+            Algorithm::Sha256 => "SHA256",
+            Algorithm::Sha512 => "SHA512",
+        }
+    }
+
+    /// Reads `SHA1`, `sha-256` and the like.
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.replace('-', "");
+        Algorithm::ALL
+            .into_iter()
+            .find(|algorithm| algorithm.name().eq_ignore_ascii_case(&name))
+    }
+}
+
+/// How codes are derived from the secret, as agreed with the service.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TotpParameters {
+    pub digits: u32,
+    /// Seconds each code is valid for.
+    pub period: u64,
+    pub algorithm: Algorithm,
+    /// Set for HOTP, where codes follow this counter instead of the clock and `period` is
+    /// unused.
+    pub counter: Option<u64>,
+}
+
+impl Default for TotpParameters {
+    fn default() -> Self {
+        TotpParameters {
+            digits: DEFAULT_DIGITS,
+            period: DEFAULT_PERIOD,
+            algorithm: Algorithm::Sha1,
+            counter: None,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum TotpError {
+    EmptySecret,
+    /// The secret has a character outside of the base32 alphabet.
+    InvalidSecret(char),
+    InvalidDigits,
+    InvalidPeriod,
+    InvalidCounter,
+    UnknownAlgorithm(String),
+    UnknownOption(String),
+}
+
+impl fmt::Display for TotpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TotpError::EmptySecret => write!(f, "the secret is empty"),
+            TotpError::InvalidSecret(c) => write!(f, "{c:?} is not a base32 character"),
+            TotpError::InvalidDigits => write!(f, "codes have 6 to 10 digits"),
+            TotpError::InvalidPeriod => write!(f, "the period must be at least a second"),
+            TotpError::InvalidCounter => write!(f, "the counter must be a positive number"),
+            TotpError::UnknownAlgorithm(name) => {
+                write!(f, "unknown algorithm {name:?}, use SHA1, SHA256 or SHA512")
+            }
+            TotpError::UnknownOption(option) => write!(f, "unknown option {option:?}"),
+        }
+    }
+}
+
+impl TotpParameters {
+    pub fn validate(&self) -> Result<(), TotpError> {
+        if !(6..=10).contains(&self.digits) {
+            return Err(TotpError::InvalidDigits);
+        }
+        if self.period == 0 {
+            return Err(TotpError::InvalidPeriod);
+        }
+
+        Ok(())
+    }
+}
+
+/// Reads a setup as typed in the entry form: the base32 secret, spaces allowed, optionally
+/// followed by comma separated options, e.g. `JBSW Y3DP EHPK 3PXP, digits=8, period=60`.
+///
+/// Returns the secret in canonical form: uppercase, without spaces or padding.
+pub fn parse_setup(setup: &str) -> Result<(String, TotpParameters), TotpError> {
+    let mut parts = setup.split(',').map(str::trim);
+    let secret = normalize_secret(parts.next().unwrap_or_default())?;
+
+    let mut parameters = TotpParameters::default();
+    for option in parts.filter(|option| !option.is_empty()) {
+        let (name, value) = option
+            .split_once('=')
+            .map(|(name, value)| (name.trim(), value.trim()))
+            .ok_or_else(|| TotpError::UnknownOption(option.to_string()))?;
+
+        match name.to_ascii_lowercase().as_str() {
+            "digits" => parameters.digits = value.parse().map_err(|_| TotpError::InvalidDigits)?,
+            "period" => parameters.period = value.parse().map_err(|_| TotpError::InvalidPeriod)?,
+            "algorithm" => {
+                parameters.algorithm = Algorithm::from_name(value)
+                    .ok_or_else(|| TotpError::UnknownAlgorithm(value.to_string()))?
+            }
+            "counter" => {
+                parameters.counter = Some(value.parse().map_err(|_| TotpError::InvalidCounter)?)
+            }
+            _ => return Err(TotpError::UnknownOption(name.to_string())),
+        }
+    }
+    parameters.validate()?;
+
+    Ok((secret, parameters))
+}
+
+/// The reverse of [`parse_setup`], leaving out options that have their default value.
+pub fn format_setup(secret: &str, parameters: &TotpParameters) -> String {
+    let defaults = TotpParameters::default();
+    let mut setup = secret.to_string();
+
+    if parameters.digits != defaults.digits {
+        setup.push_str(&format!(", digits={}", parameters.digits));
+    }
+    if parameters.period != defaults.period {
+        setup.push_str(&format!(", period={}", parameters.period));
+    }
+    if parameters.algorithm != defaults.algorithm {
+        setup.push_str(&format!(", algorithm={}", parameters.algorithm.name()));
+    }
+    if let Some(counter) = parameters.counter {
+        setup.push_str(&format!(", counter={counter}"));
+    }
+
+    setup
+}
+
+/// Uppercases `secret` and drops spaces, dashes and padding, checking it decodes.
+pub fn normalize_secret(secret: &str) -> Result<String, TotpError> {
+    let normalized: String = secret
+        .chars()
+        .filter(|c| !matches!(c, ' ' | '-' | '='))
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+
+    if decode_base32(&normalized)?.is_empty() {
+        return Err(TotpError::EmptySecret);
+    }
+
+    Ok(normalized)
+}
+
+/// Decodes RFC 4648 base32, without padding.
+pub fn decode_base32(encoded: &str) -> Result<Vec<u8>, TotpError> {
+    let mut decoded = Vec::with_capacity(encoded.len() * 5 / 8);
+    let mut buffer: u64 = 0;
+    let mut bits = 0;
+
+    for c in encoded.chars() {
+        let value = BASE32_ALPHABET
+            .iter()
+            .position(|letter| char::from(*letter) == c.to_ascii_uppercase())
+            .ok_or(TotpError::InvalidSecret(c))?;
+
+        buffer = (buffer << 5) | value as u64;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            decoded.push((buffer >> bits) as u8);
+        }
+    }
+
+    Ok(decoded)
+}
+
+/// Encodes `bytes` as RFC 4648 base32, without padding.
+pub fn encode_base32(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(5) * 8);
+    let mut buffer: u64 = 0;
+    let mut bits = 0;
+
+    for byte in bytes {
+        buffer = (buffer << 8) | u64::from(*byte);
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            encoded.push(char::from(BASE32_ALPHABET[(buffer >> bits) as usize & 31]));
+        }
+    }
+    if bits > 0 {
+        encoded.push(char::from(
+            BASE32_ALPHABET[(buffer << (5 - bits)) as usize & 31],
+        ));
+    }
+
+    encoded
+}
+
+/// The RFC 4226 code for `counter`, zero padded to `digits`.
+pub fn hotp(secret: &[u8], counter: u64, digits: u32, algorithm: Algorithm) -> String {
+    let digest = match algorithm {
+        Algorithm::Sha1 => authenticate::<Hmac<Sha1>>(secret, counter),
+        Algorithm::Sha256 => authenticate::<Hmac<Sha256>>(secret, counter),
+        Algorithm::Sha512 => authenticate::<Hmac<Sha512>>(secret, counter),
+    };
+
+    // Dynamic truncation: four bytes at the offset given by the low nibble of the last byte.
+    let offset = usize::from(digest[digest.len() - 1] & 0x0f);
+    let truncated = u32::from_be_bytes([
+        digest[offset] & 0x7f,
+        digest[offset + 1],
+        digest[offset + 2],
+        digest[offset + 3],
+    ]);
+
+    let code = u64::from(truncated) % 10u64.pow(digits);
+    format!("{code:0width$}", width = digits as usize)
+}
+
+/// The RFC 6238 code at `unix_time`, in seconds.
+pub fn totp(secret: &[u8], parameters: &TotpParameters, unix_time: u64) -> String {
+    hotp(
+        secret,
+        unix_time / parameters.period,
+        parameters.digits,
+        parameters.algorithm,
+    )
+}
+
+/// The code to use at `unix_time`: the HOTP code of the counter if there is one, the TOTP
+/// code otherwise.
+pub fn code(secret: &[u8], parameters: &TotpParameters, unix_time: u64) -> String {
+    match parameters.counter {
+        Some(counter) => hotp(secret, counter, parameters.digits, parameters.algorithm),
+        None => totp(secret, parameters, unix_time),
+    }
+}
+
+/// Seconds until the code shown at `unix_time` is replaced.
+pub fn seconds_left(parameters: &TotpParameters, unix_time: u64) -> u64 {
+    parameters.period - unix_time % parameters.period
+}
+
+/// The MAC of `counter` as eight big endian bytes.
+fn authenticate<M: Mac + KeyInit>(key: &[u8], counter: u64) -> Vec<u8> {
+    let mut mac = <M as KeyInit>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(&counter.to_be_bytes());
+
+    mac.finalize().into_bytes().to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codes_match_the_rfc_6238_test_vectors() {
+        let sha1 = b"12345678901234567890";
+        let sha256 = b"12345678901234567890123456789012";
+        let sha512 = b"1234567890123456789012345678901234567890123456789012345678901234";
+
+        for (time, expected) in [
+            (59, ["94287082", "46119246", "90693936"]),
+            (1111111109, ["07081804", "68084774", "25091201"]),
+            (1234567890, ["89005924", "91819424", "93441116"]),
+            (20000000000, ["65353130", "77737706", "47863826"]),
+        ] {
+            for ((secret, algorithm), expected) in [
+                (&sha1[..], Algorithm::Sha1),
+                (&sha256[..], Algorithm::Sha256),
+                (&sha512[..], Algorithm::Sha512),
+            ]
+            .into_iter()
+            .zip(expected)
+            {
+                let parameters = TotpParameters {
+                    digits: 8,
+                    algorithm,
+                    ..Default::default()
+                };
+                assert_eq!(
+                    totp(secret, &parameters, time),
+                    expected,
+                    "{time} {algorithm:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn base32_round_trips() {
+        assert_eq!(encode_base32(b"Hello!\xde\xad\xbe\xef"), "JBSWY3DPEHPK3PXP");
+        assert_eq!(
+            decode_base32("JBSWY3DPEHPK3PXP").unwrap(),
+            b"Hello!\xde\xad\xbe\xef"
+        );
+        assert_eq!(decode_base32("jbswy3dp").unwrap(), b"Hello");
+        assert_eq!(decode_base32("JBSW1"), Err(TotpError::InvalidSecret('1')));
+
+        for length in 0..12 {
+            let bytes: Vec<u8> = (0..length).map(|i: u8| i.wrapping_mul(37)).collect();
+            assert_eq!(decode_base32(&encode_base32(&bytes)).unwrap(), bytes);
+        }
+    }
+
+    #[test]
+    fn setups_parse_and_format_back() {
+        let (secret, parameters) = parse_setup("jbsw y3dp ehpk 3pxp").unwrap();
+        assert_eq!(secret, "JBSWY3DPEHPK3PXP");
+        assert_eq!(parameters, TotpParameters::default());
+        assert_eq!(format_setup(&secret, &parameters), "JBSWY3DPEHPK3PXP");
+
+        let setup = "JBSWY3DPEHPK3PXP, digits=8, period=60, algorithm=SHA256, counter=3";
+        let (secret, parameters) = parse_setup(setup).unwrap();
+        assert_eq!(parameters.digits, 8);
+        assert_eq!(parameters.period, 60);
+        assert_eq!(parameters.algorithm, Algorithm::Sha256);
+        assert_eq!(parameters.counter, Some(3));
+        assert_eq!(format_setup(&secret, &parameters), setup);
+
+        assert_eq!(parse_setup(""), Err(TotpError::EmptySecret));
+        assert_eq!(parse_setup("JBSW, digits=4"), Err(TotpError::InvalidDigits));
+        assert_eq!(parse_setup("JBSW, period=0"), Err(TotpError::InvalidPeriod));
+        assert_eq!(
+            parse_setup("JBSW, colour=red"),
+            Err(TotpError::UnknownOption(String::from("colour")))
+        );
+    }
+}
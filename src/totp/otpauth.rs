@@ -0,0 +1,356 @@
+use std::fmt;
+
+use base64::{
+    prelude::{BASE64_STANDARD, BASE64_STANDARD_NO_PAD, BASE64_URL_SAFE, BASE64_URL_SAFE_NO_PAD},
+    Engine,
+};
+
+use super::{
+    encode_base32, migration, normalize_secret, Algorithm, TotpError, TotpParameters,
+    DEFAULT_DIGITS,
+};
+
+/// A one-time password generator read from an authenticator export.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OtpAccount {
+    /// The service, e.g. `GitHub`. May be empty.
+    pub issuer: String,
+    /// The account at the service, usually a username or an email address.
+    pub account: String,
+    /// Base32, uppercase and without padding.
+    pub secret: String,
+    pub parameters: TotpParameters,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ImportError {
+    /// Neither an `otpauth://` nor an `otpauth-migration://` URI.
+    UnknownScheme,
+    /// An `otpauth://` URI for something other than `totp` or `hotp`.
+    UnknownType(String),
+    MissingSecret,
+    /// A HOTP URI without its `counter`.
+    MissingCounter,
+    InvalidParameter(&'static str),
+    InvalidSecret(TotpError),
+    /// The `data` of a migration URI is not base64 of a migration payload.
+    InvalidPayload,
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImportError::UnknownScheme => {
+                write!(f, "not an otpauth:// or otpauth-migration:// URI")
+            }
+            ImportError::UnknownType(kind) => {
+                write!(f, "unknown type {kind:?}, expected totp or hotp")
+            }
+            ImportError::MissingSecret => write!(f, "the URI has no secret"),
+            ImportError::MissingCounter => write!(f, "HOTP URIs need a counter"),
+            ImportError::InvalidParameter(name) => write!(f, "invalid `{name}` parameter"),
+            ImportError::InvalidSecret(why) => write!(f, "invalid secret: {why}"),
+            ImportError::InvalidPayload => write!(f, "the migration data cannot be read"),
+        }
+    }
+}
+
+/// Reads every URI of `text`, one per line, skipping blank lines.
+///
+/// Returns what could be read, and the line number and error of every line that could not.
+pub fn parse_lines(text: &str) -> (Vec<OtpAccount>, Vec<(usize, ImportError)>) {
+    let mut accounts = vec![];
+    let mut errors = vec![];
+
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        match parse_uri(line) {
+            Ok(parsed) => accounts.extend(parsed),
+            Err(why) => errors.push((index + 1, why)),
+        }
+    }
+
+    (accounts, errors)
+}
+
+/// Reads an `otpauth://` key URI, or an `otpauth-migration://` export of several accounts.
+pub fn parse_uri(uri: &str) -> Result<Vec<OtpAccount>, ImportError> {
+    if let Some(rest) = uri.strip_prefix("otpauth://") {
+        parse_key_uri(rest).map(|account| vec![account])
+    } else if let Some(rest) = uri.strip_prefix("otpauth-migration://") {
+        parse_migration_uri(rest)
+    } else {
+        Err(ImportError::UnknownScheme)
+    }
+}
+
+/// Reads `<type>/<label>?<parameters>`, as in the Key Uri Format of Google Authenticator:
+/// the label is `<issuer>:<account>` or just `<account>`.
+fn parse_key_uri(uri: &str) -> Result<OtpAccount, ImportError> {
+    let (kind, rest) = uri.split_once('/').unwrap_or((uri, ""));
+    let (label, query) = rest.split_once('?').unwrap_or((rest, ""));
+
+    let label = percent_decode(label);
+    let (mut issuer, account) = match label.split_once(':') {
+        Some((issuer, account)) => (issuer.trim().to_string(), account.trim().to_string()),
+        None => (String::new(), label.trim().to_string()),
+    };
+
+    let mut secret = None;
+    let mut parameters = TotpParameters::default();
+    let mut counter = None;
+
+    for (name, value) in query_pairs(query) {
+        match name.as_str() {
+            "secret" => secret = Some(value),
+            // The parameter wins over the label, which some apps fill with the account only.
+            "issuer" if !value.is_empty() => issuer = value,
+            "algorithm" => {
+                parameters.algorithm = Algorithm::from_name(&value)
+                    .ok_or(ImportError::InvalidParameter("algorithm"))?
+            }
+            "digits" => {
+                parameters.digits = value
+                    .parse()
+                    .map_err(|_| ImportError::InvalidParameter("digits"))?
+            }
+            "period" => {
+                parameters.period = value
+                    .parse()
+                    .map_err(|_| ImportError::InvalidParameter("period"))?
+            }
+            "counter" => {
+                counter = Some(
+                    value
+                        .parse()
+                        .map_err(|_| ImportError::InvalidParameter("counter"))?,
+                )
+            }
+            _ => {}
+        }
+    }
+
+    match kind.to_ascii_lowercase().as_str() {
+        "totp" => {}
+        "hotp" => parameters.counter = Some(counter.ok_or(ImportError::MissingCounter)?),
+        _ => return Err(ImportError::UnknownType(kind.to_string())),
+    }
+
+    let secret = normalize_secret(&secret.ok_or(ImportError::MissingSecret)?)
+        .map_err(ImportError::InvalidSecret)?;
+    parameters.validate().map_err(ImportError::InvalidSecret)?;
+
+    Ok(OtpAccount {
+        issuer,
+        account,
+        secret,
+        parameters,
+    })
+}
+
+/// Reads `offline?data=<base64 of a protobuf payload>`.
+fn parse_migration_uri(uri: &str) -> Result<Vec<OtpAccount>, ImportError> {
+    let (_, query) = uri.split_once('?').ok_or(ImportError::InvalidPayload)?;
+    let data = query_pairs(query)
+        .find(|(name, _)| name == "data")
+        .map(|(_, data)| data)
+        .ok_or(ImportError::InvalidPayload)?;
+
+    // Apps disagree on padding and on which alphabet to use, and some leave `+` unescaped,
+    // which the query decoding turned into a space.
+    let data = data.trim().replace(' ', "+");
+    let payload = [
+        BASE64_STANDARD,
+        BASE64_STANDARD_NO_PAD,
+        BASE64_URL_SAFE,
+        BASE64_URL_SAFE_NO_PAD,
+    ]
+    .iter()
+    .find_map(|engine| engine.decode(&data).ok())
+    .ok_or(ImportError::InvalidPayload)?;
+
+    let exported = migration::decode(&payload).ok_or(ImportError::InvalidPayload)?;
+
+    exported
+        .into_iter()
+        .map(|otp| {
+            let (issuer, account) = match otp.name.split_once(':') {
+                Some((prefix, account)) if otp.issuer.is_empty() || prefix == otp.issuer => {
+                    (prefix.to_string(), account.trim().to_string())
+                }
+                _ => (otp.issuer, otp.name),
+            };
+            let parameters = TotpParameters {
+                algorithm: otp.algorithm.unwrap_or_default(),
+                digits: otp.digits.unwrap_or(DEFAULT_DIGITS),
+                counter: otp.hotp.then_some(otp.counter),
+                ..Default::default()
+            };
+            if otp.secret.is_empty() {
+                return Err(ImportError::MissingSecret);
+            }
+
+            Ok(OtpAccount {
+                issuer,
+                account,
+                secret: encode_base32(&otp.secret),
+                parameters,
+            })
+        })
+        .collect()
+}
+
+/// The `name=value` pairs of a query string, percent decoded.
+fn query_pairs(query: &str) -> impl Iterator<Item = (String, String)> + '_ {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (
+                percent_decode(name).to_ascii_lowercase(),
+                percent_decode(value),
+            )
+        })
+}
+
+/// Decodes `%XX` escapes, and `+` as a space as in form encoding. Invalid escapes and
+/// invalid UTF-8 are kept as they are.
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+
+    while index < bytes.len() {
+        let escaped = (bytes[index] == b'%')
+            .then(|| bytes.get(index + 1..index + 3))
+            .flatten()
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+        match (escaped, bytes[index]) {
+            (Some(byte), _) => {
+                decoded.push(byte);
+                index += 3;
+            }
+            (None, b'+') => {
+                decoded.push(b' ');
+                index += 1;
+            }
+            (None, byte) => {
+                decoded.push(byte);
+                index += 1;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key_uris_are_read() {
+        let accounts = parse_uri(
+            "otpauth://totp/ACME%20Co:john.doe@email.com?secret=HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ\
+             &issuer=ACME%20Co&algorithm=SHA256&digits=8&period=60",
+        )
+        .unwrap();
+
+        assert_eq!(
+            accounts,
+            vec![OtpAccount {
+                issuer: String::from("ACME Co"),
+                account: String::from("john.doe@email.com"),
+                secret: String::from("HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ"),
+                parameters: TotpParameters {
+                    digits: 8,
+                    period: 60,
+                    algorithm: Algorithm::Sha256,
+                    counter: None,
+                },
+            }]
+        );
+
+        let hotp = &parse_uri("otpauth://hotp/alice?secret=jbswy3dpehpk3pxp&counter=7").unwrap()[0];
+        assert_eq!(hotp.issuer, "");
+        assert_eq!(hotp.account, "alice");
+        assert_eq!(hotp.parameters.counter, Some(7));
+    }
+
+    #[test]
+    fn broken_uris_are_reported_per_line() {
+        let text = "\
+            otpauth://totp/Good?secret=JBSWY3DPEHPK3PXP\n\
+            \n\
+            https://example.com\n\
+            otpauth://hotp/NoCounter?secret=JBSWY3DPEHPK3PXP\n\
+            otpauth://totp/NoSecret\n\
+            otpauth://totp/BadSecret?secret=not-base32!\n\
+            otpauth://motp/Other?secret=JBSWY3DPEHPK3PXP\n";
+
+        let (accounts, errors) = parse_lines(text);
+
+        assert_eq!(accounts.len(), 1);
+        assert_eq!(
+            errors,
+            vec![
+                (3, ImportError::UnknownScheme),
+                (4, ImportError::MissingCounter),
+                (5, ImportError::MissingSecret),
+                (6, ImportError::InvalidSecret(TotpError::InvalidSecret('!'))),
+                (7, ImportError::UnknownType(String::from("motp"))),
+            ]
+        );
+    }
+
+    #[test]
+    fn migration_payloads_are_read() {
+        // Two accounts, as exported by Google Authenticator: a TOTP one for
+        // "Example:alice@example.com" and a HOTP one at counter 3.
+        let payload = migration::tests::payload(&[
+            (
+                b"Hello!\xde\xad\xbe\xef",
+                "Example:alice@example.com",
+                "Example",
+                1,
+                1,
+                2,
+                0,
+            ),
+            (b"12345678901234567890", "bob", "Bank", 2, 2, 1, 3),
+        ]);
+        let uri = format!(
+            "otpauth-migration://offline?data={}",
+            BASE64_STANDARD
+                .encode(payload)
+                .replace('+', "%2B")
+                .replace('/', "%2F")
+                .replace('=', "%3D")
+        );
+
+        let accounts = parse_uri(&uri).unwrap();
+
+        assert_eq!(accounts.len(), 2);
+        assert_eq!(accounts[0].issuer, "Example");
+        assert_eq!(accounts[0].account, "alice@example.com");
+        assert_eq!(accounts[0].secret, "JBSWY3DPEHPK3PXP");
+        assert_eq!(accounts[0].parameters, TotpParameters::default());
+        assert_eq!(accounts[1].issuer, "Bank");
+        assert_eq!(accounts[1].account, "bob");
+        assert_eq!(accounts[1].parameters.algorithm, Algorithm::Sha256);
+        assert_eq!(accounts[1].parameters.digits, 8);
+        assert_eq!(accounts[1].parameters.counter, Some(3));
+
+        assert_eq!(
+            parse_uri("otpauth-migration://offline?data=%%%"),
+            Err(ImportError::InvalidPayload)
+        );
+    }
+}
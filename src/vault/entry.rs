@@ -0,0 +1,169 @@
+use chrono::{DateTime, SubsecRound, Utc};
+use secrecy::zeroize::Zeroize;
+
+use crate::totp::TotpParameters;
+
+/// A named value attached to an entry, e.g. a PIN or a recovery code.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CustomField {
+    pub name: String,
+    /// Base64 of the value sealed with the master key when `secret`, the value itself otherwise.
+    pub value: String,
+    pub secret: bool,
+}
+
+/// A password an entry used to have, kept in case a change has to be rolled back.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PreviousPassword {
+    /// Base64 of the password sealed with the master key.
+    pub password: String,
+    pub replaced: DateTime<Utc>,
+}
+
+/// The TOTP generator of an account with two-factor authentication.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OneTimePassword {
+    /// Base64 of the base32 secret sealed with the master key.
+    pub secret: String,
+    pub parameters: TotpParameters,
+}
+
+/// A single account stored in a vault.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Entry {
+    pub label: String,
+    /// Base64 of the password sealed with the master key, or empty if the entry has none.
+    pub password: String,
+    pub username: String,
+    pub urls: Vec<String>,
+    pub notes: String,
+    pub tags: Vec<String>,
+    pub custom_fields: Vec<CustomField>,
+    pub totp: Option<OneTimePassword>,
+    /// Earlier passwords, the most recently replaced first.
+    pub history: Vec<PreviousPassword>,
+    /// Strength score of the password when it was set, see [`crate::strength`].
+    pub strength: Option<u8>,
+    /// Unknown for entries written before timestamps were kept.
+    pub created: Option<DateTime<Utc>>,
+    pub modified: Option<DateTime<Utc>>,
+    pub last_used: Option<DateTime<Utc>>,
+}
+
+impl Entry {
+    /// Whether the entry has a password, which those imported for their one-time password
+    /// alone don't.
+    pub fn has_password(&self) -> bool {
+        !self.password.is_empty()
+    }
+
+    /// A freshly created entry, with its creation and modification time set to now.
+    pub fn new(label: String, password: String) -> Self {
+        let now = now();
+
+        Entry {
+            label,
+            password,
+            created: Some(now),
+            modified: Some(now),
+            ..Default::default()
+        }
+    }
+
+    /// Records that the entry was changed just now.
+    pub fn touch(&mut self) {
+        self.modified = Some(now());
+    }
+
+    /// Files `previous` away as replaced just now, keeping at most `depth` old passwords.
+    pub fn remember_password(&mut self, previous: String, depth: usize) {
+        self.history.insert(
+            0,
+            PreviousPassword {
+                password: previous,
+                replaced: now(),
+            },
+        );
+        self.history.truncate(depth);
+    }
+}
+
+/// Overwrites everything the entry says about the account, e.g. before the vault is locked.
+impl Zeroize for Entry {
+    fn zeroize(&mut self) {
+        self.label.zeroize();
+        self.password.zeroize();
+        self.username.zeroize();
+        self.urls.zeroize();
+        self.notes.zeroize();
+        self.tags.zeroize();
+        for field in self.custom_fields.iter_mut() {
+            field.name.zeroize();
+            field.value.zeroize();
+        }
+        self.custom_fields.clear();
+        if let Some(totp) = self.totp.as_mut() {
+            totp.secret.zeroize();
+        }
+        self.totp = None;
+        for previous in self.history.iter_mut() {
+            previous.password.zeroize();
+        }
+        self.history.clear();
+    }
+}
+
+/// The current time, to the second as it is stored in the vault.
+pub fn now() -> DateTime<Utc> {
+    Utc::now().trunc_subsecs(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn history_keeps_the_most_recent_passwords() {
+        let mut entry = Entry::new(String::from("mail"), String::from("first"));
+
+        for password in ["second", "third", "fourth"] {
+            let previous = std::mem::replace(&mut entry.password, password.to_string());
+            entry.remember_password(previous, 2);
+        }
+
+        let kept: Vec<_> = entry
+            .history
+            .iter()
+            .map(|old| old.password.as_str())
+            .collect();
+        assert_eq!(kept, ["third", "second"]);
+
+        entry.remember_password(String::from("fourth"), 0);
+        assert!(entry.history.is_empty());
+    }
+
+    #[test]
+    fn zeroizing_leaves_nothing_about_the_account() {
+        let mut entry = Entry::new(String::from("mail"), String::from("sealed"));
+        entry.username = String::from("alice");
+        entry.urls = vec![String::from("https://mail.example.com")];
+        entry.custom_fields = vec![CustomField {
+            name: String::from("PIN"),
+            value: String::from("1234"),
+            secret: false,
+        }];
+        entry.totp = Some(OneTimePassword {
+            secret: String::from("sealed secret"),
+            parameters: TotpParameters::default(),
+        });
+        entry.remember_password(String::from("older"), 1);
+
+        entry.zeroize();
+
+        assert_eq!(entry.label, "");
+        assert_eq!(entry.password, "");
+        assert_eq!(entry.username, "");
+        assert!(entry.urls.is_empty() && entry.custom_fields.is_empty());
+        assert!(entry.totp.is_none() && entry.history.is_empty());
+    }
+}
@@ -0,0 +1,160 @@
+use std::{
+    fs,
+    io::{self, ErrorKind, Write},
+    path::{Path, PathBuf},
+};
+
+use super::{persistence, store::VaultStore, VaultError};
+
+/// Keeps one file per account in a directory, with rotated backups next to each vault.
+pub struct FileStore {
+    directory_path: PathBuf,
+    backup_count: usize,
+}
+
+impl FileStore {
+    /// Keeps `backup_count` previous versions of every vault, none with `0`.
+    pub fn new(directory_path: PathBuf, backup_count: usize) -> Self {
+        FileStore {
+            directory_path,
+            backup_count,
+        }
+    }
+
+    fn vault_path(&self, login: &str) -> PathBuf {
+        self.directory_path.join(login)
+    }
+}
+
+impl VaultStore for FileStore {
+    fn load(&self, login: &str) -> Result<String, VaultError> {
+        let path = self.vault_path(login);
+        let contents = fs::read_to_string(&path).map_err(not_found_as_missing_account)?;
+        // Earlier versions wrote vaults readable by everyone.
+        persistence::tighten_vault_files(&path)?;
+
+        Ok(contents)
+    }
+
+    fn save(&mut self, login: &str, contents: &str) -> Result<(), VaultError> {
+        let path = self.vault_path(login);
+        if !path.exists() {
+            return Err(VaultError::AccountNotFound);
+        }
+
+        persistence::write(&path, contents.as_bytes(), self.backup_count)?;
+        Ok(())
+    }
+
+    fn create(&mut self, login: &str, contents: &str) -> Result<(), VaultError> {
+        persistence::create_private_directory(&self.directory_path)?;
+
+        let mut file = persistence::private_open_options()
+            .write(true)
+            .create_new(true)
+            .open(self.vault_path(login))
+            .map_err(|why| match why.kind() {
+                ErrorKind::AlreadyExists => VaultError::AccountExists,
+                _ => VaultError::Io(why),
+            })?;
+
+        file.write_all(contents.as_bytes())?;
+        file.sync_all()?;
+
+        Ok(())
+    }
+
+    fn list_accounts(&self) -> Result<Vec<String>, VaultError> {
+        let entries = match fs::read_dir(&self.directory_path) {
+            Ok(entries) => entries,
+            Err(why) if why.kind() == ErrorKind::NotFound => return Ok(vec![]),
+            Err(why) => return Err(VaultError::Io(why)),
+        };
+
+        let mut logins = vec![];
+        for entry in entries {
+            let entry = entry?;
+            let Ok(file_name) = entry.file_name().into_string() else {
+                continue;
+            };
+
+            // Backups and temporary files carry a suffix that logins cannot contain.
+            if entry.file_type()?.is_file() && !file_name.contains('.') {
+                logins.push(file_name);
+            }
+        }
+        logins.sort();
+
+        Ok(logins)
+    }
+
+    fn delete(&mut self, login: &str) -> Result<(), VaultError> {
+        let path = self.vault_path(login);
+        fs::remove_file(&path).map_err(not_found_as_missing_account)?;
+
+        // Backups are numbered without gaps, including ones kept under a larger count.
+        for index in 1.. {
+            let backup_path = persistence::backup_path(&path, index);
+            if !backup_path.exists() {
+                break;
+            }
+            remove_if_exists(&backup_path)?;
+        }
+        remove_if_exists(&persistence::preferences_path(&path))?;
+
+        Ok(())
+    }
+
+    fn load_preferences(&self, login: &str) -> Result<Option<String>, VaultError> {
+        match fs::read_to_string(persistence::preferences_path(&self.vault_path(login))) {
+            Ok(contents) => Ok(Some(contents)),
+            Err(why) if why.kind() == ErrorKind::NotFound => Ok(None),
+            Err(why) => Err(VaultError::Io(why)),
+        }
+    }
+
+    fn save_preferences(&mut self, login: &str, contents: &str) -> Result<(), VaultError> {
+        let path = self.vault_path(login);
+        if !path.exists() {
+            return Err(VaultError::AccountNotFound);
+        }
+
+        persistence::write_without_backup(
+            &persistence::preferences_path(&path),
+            contents.as_bytes(),
+        )?;
+        Ok(())
+    }
+
+    fn backup_count(&self) -> usize {
+        self.backup_count
+    }
+
+    fn load_backup(&self, login: &str, index: usize) -> Result<String, VaultError> {
+        let path = persistence::backup_path(&self.vault_path(login), index);
+
+        fs::read_to_string(path).map_err(|why| match why.kind() {
+            ErrorKind::NotFound => VaultError::NoUsableBackup,
+            _ => VaultError::Io(why),
+        })
+    }
+
+    fn restore_backup(&mut self, login: &str, index: usize) -> Result<(), VaultError> {
+        persistence::restore(&self.vault_path(login), index)?;
+        Ok(())
+    }
+}
+
+fn not_found_as_missing_account(why: io::Error) -> VaultError {
+    match why.kind() {
+        ErrorKind::NotFound => VaultError::AccountNotFound,
+        _ => VaultError::Io(why),
+    }
+}
+
+fn remove_if_exists(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path) {
+        Err(why) if why.kind() != ErrorKind::NotFound => Err(why),
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,181 @@
+use std::fmt;
+
+use argon2::{Algorithm, Version};
+use base64::{prelude::BASE64_STANDARD, Engine};
+
+use crate::crypto_utils::{KdfParams, SALT_LENGTH};
+
+/// Version of the on-disk vault layout written by this build.
+///
+/// 0. no header, one `label=base64` line per entry
+/// 1. header followed by the same plaintext entry lines
+/// 2. header followed by every entry sealed into a single blob
+/// 3. same as 2, with `\`, `=` and line breaks escaped inside entries
+/// 4. same as 3, with the entries in the trash following a `[trash]` line
+/// 5. same as 4, with each entry a `;` separated list of named fields
+/// 6. same as 5, with the previous passwords of an entry in `history` fields
+/// 7. same as 6, with the strength score of the password in a `strength` field
+/// 8. same as 7, with the TOTP generator of an entry in a `totp` field
+/// 9. same as 8, with a fifth part in `totp` fields holding the counter of HOTP generators
+pub const FORMAT_VERSION: u32 = 9;
+
+const MAGIC: &str = "rusty-lock";
+
+/// Upper bounds on the KDF costs a header may ask for, in KiB, passes and lanes. The header is
+/// read before the password can be checked, so a crafted one must not make unlocking allocate
+/// or compute without limit.
+const MAX_M_COST: u32 = 1024 * 1024;
+const MAX_T_COST: u32 = 32;
+const MAX_P_COST: u32 = 16;
+
+/// First line of every vault file, describing its layout and how its master key is derived.
+///
+/// Serialized as `rusty-lock;format=9;kdf=argon2id;v=19;m=19456;t=2;p=1;salt=<base64>;check=<base64>`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VaultHeader {
+    pub format_version: u32,
+    pub kdf: KdfParams,
+    /// A known plaintext encrypted with the master key, used to reject wrong passwords.
+    pub key_check: Option<Vec<u8>>,
+}
+
+#[derive(Debug)]
+pub enum HeaderError {
+    MissingField(&'static str),
+    InvalidField(&'static str),
+}
+
+impl fmt::Display for HeaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HeaderError::MissingField(name) => write!(f, "vault header is missing `{name}`"),
+            HeaderError::InvalidField(name) => write!(f, "vault header has an invalid `{name}`"),
+        }
+    }
+}
+
+impl VaultHeader {
+    /// Header for a brand new vault, with a fresh random salt.
+    pub fn generate() -> Self {
+        VaultHeader {
+            format_version: FORMAT_VERSION,
+            kdf: KdfParams::generate(),
+            key_check: None,
+        }
+    }
+
+    /// Header implied by vault files written before headers were introduced.
+    pub fn legacy() -> Self {
+        VaultHeader {
+            format_version: 0,
+            kdf: KdfParams::legacy(),
+            key_check: None,
+        }
+    }
+
+    /// Returns `Ok(None)` when `line` is not a header at all, i.e. a legacy vault.
+    pub fn parse(line: &str) -> Result<Option<Self>, HeaderError> {
+        let mut fields = line.split(';');
+        if fields.next() != Some(MAGIC) {
+            return Ok(None);
+        }
+
+        let mut format_version = None;
+        let mut algorithm = None;
+        let mut version = None;
+        let mut m_cost = None;
+        let mut t_cost = None;
+        let mut p_cost = None;
+        let mut salt = None;
+        let mut key_check = None;
+
+        for field in fields {
+            let Some((key, value)) = field.split_once('=') else {
+                continue;
+            };
+
+            match key {
+                "format" => format_version = Some(parse_number(value, "format")?),
+                "kdf" => {
+                    algorithm = Some(
+                        value
+                            .parse::<Algorithm>()
+                            .map_err(|_| HeaderError::InvalidField("kdf"))?,
+                    )
+                }
+                "v" => {
+                    version = Some(
+                        Version::try_from(parse_number(value, "v")?)
+                            .map_err(|_| HeaderError::InvalidField("v"))?,
+                    )
+                }
+                "m" => m_cost = Some(parse_cost(value, "m", MAX_M_COST)?),
+                "t" => t_cost = Some(parse_cost(value, "t", MAX_T_COST)?),
+                "p" => p_cost = Some(parse_cost(value, "p", MAX_P_COST)?),
+                "salt" => {
+                    let decoded = BASE64_STANDARD
+                        .decode(value)
+                        .map_err(|_| HeaderError::InvalidField("salt"))?;
+                    salt = Some(
+                        <[u8; SALT_LENGTH]>::try_from(decoded)
+                            .map_err(|_| HeaderError::InvalidField("salt"))?,
+                    );
+                }
+                "check" => {
+                    key_check = Some(
+                        BASE64_STANDARD
+                            .decode(value)
+                            .map_err(|_| HeaderError::InvalidField("check"))?,
+                    )
+                }
+                _ => {}
+            }
+        }
+
+        Ok(Some(VaultHeader {
+            format_version: format_version.ok_or(HeaderError::MissingField("format"))?,
+            kdf: KdfParams {
+                algorithm: algorithm.ok_or(HeaderError::MissingField("kdf"))?,
+                version: version.ok_or(HeaderError::MissingField("v"))?,
+                m_cost: m_cost.ok_or(HeaderError::MissingField("m"))?,
+                t_cost: t_cost.ok_or(HeaderError::MissingField("t"))?,
+                p_cost: p_cost.ok_or(HeaderError::MissingField("p"))?,
+                salt: salt.ok_or(HeaderError::MissingField("salt"))?,
+            },
+            key_check,
+        }))
+    }
+}
+
+impl fmt::Display for VaultHeader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{MAGIC};format={};kdf={};v={};m={};t={};p={};salt={}",
+            self.format_version,
+            self.kdf.algorithm,
+            u32::from(self.kdf.version),
+            self.kdf.m_cost,
+            self.kdf.t_cost,
+            self.kdf.p_cost,
+            BASE64_STANDARD.encode(self.kdf.salt),
+        )?;
+
+        if let Some(key_check) = &self.key_check {
+            write!(f, ";check={}", BASE64_STANDARD.encode(key_check))?;
+        }
+
+        Ok(())
+    }
+}
+
+fn parse_number(value: &str, field: &'static str) -> Result<u32, HeaderError> {
+    value.parse().map_err(|_| HeaderError::InvalidField(field))
+}
+
+fn parse_cost(value: &str, field: &'static str, max: u32) -> Result<u32, HeaderError> {
+    match parse_number(value, field)? {
+        cost @ 1.. if cost <= max => Ok(cost),
+        _ => Err(HeaderError::InvalidField(field)),
+    }
+}
@@ -0,0 +1,68 @@
+use std::collections::HashMap;
+
+use super::{store::VaultStore, VaultError};
+
+/// Keeps vaults in memory only, for tests and throwaway sessions.
+#[derive(Default)]
+pub struct MemoryStore {
+    vaults: HashMap<String, String>,
+    preferences: HashMap<String, String>,
+}
+
+impl VaultStore for MemoryStore {
+    fn load(&self, login: &str) -> Result<String, VaultError> {
+        self.vaults
+            .get(login)
+            .cloned()
+            .ok_or(VaultError::AccountNotFound)
+    }
+
+    fn save(&mut self, login: &str, contents: &str) -> Result<(), VaultError> {
+        match self.vaults.get_mut(login) {
+            Some(vault) => {
+                *vault = contents.to_string();
+                Ok(())
+            }
+            None => Err(VaultError::AccountNotFound),
+        }
+    }
+
+    fn create(&mut self, login: &str, contents: &str) -> Result<(), VaultError> {
+        if self.vaults.contains_key(login) {
+            return Err(VaultError::AccountExists);
+        }
+
+        self.vaults.insert(login.to_string(), contents.to_string());
+        Ok(())
+    }
+
+    fn list_accounts(&self) -> Result<Vec<String>, VaultError> {
+        let mut logins: Vec<String> = self.vaults.keys().cloned().collect();
+        logins.sort();
+
+        Ok(logins)
+    }
+
+    fn delete(&mut self, login: &str) -> Result<(), VaultError> {
+        self.preferences.remove(login);
+
+        self.vaults
+            .remove(login)
+            .map(|_| ())
+            .ok_or(VaultError::AccountNotFound)
+    }
+
+    fn load_preferences(&self, login: &str) -> Result<Option<String>, VaultError> {
+        Ok(self.preferences.get(login).cloned())
+    }
+
+    fn save_preferences(&mut self, login: &str, contents: &str) -> Result<(), VaultError> {
+        if !self.vaults.contains_key(login) {
+            return Err(VaultError::AccountNotFound);
+        }
+
+        self.preferences
+            .insert(login.to_string(), contents.to_string());
+        Ok(())
+    }
+}
@@ -0,0 +1,91 @@
+use base64::{prelude::BASE64_STANDARD, Engine};
+use secrecy::{zeroize::Zeroize, SecretBox, SecretString};
+
+use super::{
+    header::{VaultHeader, FORMAT_VERSION},
+    store::VaultStore,
+    Entries, RawVault, Vault, VaultError,
+};
+use crate::crypto_utils::{self, KdfParams};
+
+/// Rejects vaults written by a newer build, whose layout this one cannot know.
+pub(super) fn check_supported(header: &VaultHeader) -> Result<(), VaultError> {
+    if header.format_version > FORMAT_VERSION {
+        return Err(VaultError::UnsupportedFormat(header.format_version));
+    }
+
+    Ok(())
+}
+
+/// Rewrites `vault`, freshly unlocked from `contents` with `password`, in the current format
+/// if it was written in an older one, and under a key of its own if it still uses the salt
+/// every legacy vault shared.
+///
+/// Every older format can still be read, so upgrading means reading the entries the old
+/// way and saving them the new way. Saving rotates the original file into the backups like
+/// any earlier version, so it can be restored for a while and then ages out with the key
+/// it was sealed under.
+pub(super) fn upgrade(
+    store: &mut dyn VaultStore,
+    vault: &mut Vault,
+    contents: &str,
+    password: &SecretString,
+) -> Result<(), VaultError> {
+    let version = vault.header.format_version;
+    let shares_legacy_key = vault.header.kdf == KdfParams::legacy();
+    if version >= FORMAT_VERSION && !shares_legacy_key {
+        return Ok(());
+    }
+
+    let (mut entries, damaged) = RawVault::parse(contents)?.entries(vault.key())?;
+
+    if shares_legacy_key {
+        let kdf = KdfParams::generate();
+        let key = crypto_utils::hash_password(password, &kdf)?;
+        reseal(&mut entries, vault.key(), key.as_slice())?;
+
+        vault.header.kdf = kdf;
+        // A fresh one for the new key is written on save.
+        vault.header.key_check = None;
+        vault.key = SecretBox::new(Box::new(key.to_vec()));
+    }
+
+    vault.damaged = damaged;
+    vault.save(store, &entries)
+}
+
+/// Seals every sealed field of `entries` under `new_key` instead of `old_key`. Fields that do
+/// not open with `old_key` were unreadable already and are kept as they are.
+fn reseal(entries: &mut Entries, old_key: &[u8], new_key: &[u8]) -> Result<(), VaultError> {
+    for entry in entries.active.iter_mut().chain(entries.trash.iter_mut()) {
+        reseal_field(&mut entry.password, old_key, new_key)?;
+        for previous in entry.history.iter_mut() {
+            reseal_field(&mut previous.password, old_key, new_key)?;
+        }
+        for field in entry.custom_fields.iter_mut().filter(|field| field.secret) {
+            reseal_field(&mut field.value, old_key, new_key)?;
+        }
+        if let Some(totp) = entry.totp.as_mut() {
+            reseal_field(&mut totp.secret, old_key, new_key)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn reseal_field(sealed: &mut String, old_key: &[u8], new_key: &[u8]) -> Result<(), VaultError> {
+    let opened = BASE64_STANDARD
+        .decode(sealed.as_bytes())
+        .ok()
+        .and_then(|encrypted| crypto_utils::decrypt(&encrypted, old_key).ok())
+        .and_then(|plaintext| String::from_utf8(plaintext).ok());
+    let Some(mut plaintext) = opened else {
+        return Ok(());
+    };
+
+    let resealed = crypto_utils::encrypt(&plaintext, new_key);
+    plaintext.zeroize();
+    *sealed = BASE64_STANDARD.encode(resealed?);
+
+    Ok(())
+}
@@ -0,0 +1,541 @@
+pub mod entry;
+pub mod file_store;
+pub mod header;
+pub mod memory_store;
+mod migration;
+pub mod persistence;
+pub mod record;
+pub mod store;
+
+use std::{fmt, io, str};
+
+use base64::{prelude::BASE64_STANDARD, Engine};
+use secrecy::{
+    zeroize::{Zeroize, Zeroizing},
+    ExposeSecret, SecretBox, SecretString,
+};
+
+use crate::crypto_utils::{self, CryptoError};
+use entry::Entry;
+use header::{HeaderError, VaultHeader, FORMAT_VERSION};
+use record::DamagedLine;
+use store::VaultStore;
+
+const KEY_CHECK_PLAINTEXT: &str = "rusty-lock key check";
+
+#[derive(Debug)]
+pub enum VaultError {
+    Io(io::Error),
+    Header(HeaderError),
+    Crypto(CryptoError),
+    WrongPassword,
+    InvalidLogin,
+    AccountNotFound,
+    AccountExists,
+    Corrupted,
+    NoUsableBackup,
+    /// The vault was written by a newer version of the application, in this format.
+    UnsupportedFormat(u32),
+}
+
+impl fmt::Display for VaultError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VaultError::Io(why) => write!(f, "couldn't access the vault: {why}"),
+            VaultError::Header(why) => write!(f, "{why}"),
+            VaultError::Crypto(why) => write!(f, "{why}"),
+            VaultError::WrongPassword => write!(f, "wrong master password"),
+            VaultError::InvalidLogin => {
+                write!(f, "logins may only contain letters, digits, '-' and '_'")
+            }
+            VaultError::AccountNotFound => write!(f, "no account with this login exists"),
+            VaultError::AccountExists => write!(f, "an account with this login already exists"),
+            VaultError::Corrupted => write!(f, "the vault is damaged and cannot be decrypted"),
+            VaultError::NoUsableBackup => {
+                write!(f, "none of the backups could be opened with this password")
+            }
+            VaultError::UnsupportedFormat(version) => write!(
+                f,
+                "the vault uses format {version} from a newer rusty-lock, this version only \
+                 reads up to format {FORMAT_VERSION}; please upgrade to open it"
+            ),
+        }
+    }
+}
+
+impl From<io::Error> for VaultError {
+    fn from(value: io::Error) -> Self {
+        VaultError::Io(value)
+    }
+}
+
+impl From<CryptoError> for VaultError {
+    fn from(value: CryptoError) -> Self {
+        VaultError::Crypto(value)
+    }
+}
+
+impl From<HeaderError> for VaultError {
+    fn from(value: HeaderError) -> Self {
+        VaultError::Header(value)
+    }
+}
+
+/// Everything a vault holds: the live entries and the ones moved to the trash.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Entries {
+    pub active: Vec<Entry>,
+    pub trash: Vec<Entry>,
+}
+
+impl Zeroize for Entries {
+    fn zeroize(&mut self) {
+        self.active.zeroize();
+        self.trash.zeroize();
+    }
+}
+
+/// The vault of the logged in account, through which all of its reads and writes go.
+pub struct Vault {
+    login: String,
+    header: VaultHeader,
+    key: SecretBox<Vec<u8>>,
+    /// Lines skipped by the last [`Vault::load`], written back untouched on save.
+    damaged: Vec<DamagedLine>,
+}
+
+impl Vault {
+    pub fn login(&self) -> &str {
+        &self.login
+    }
+
+    /// The master key, also used to seal individual entries.
+    pub fn key(&self) -> &[u8] {
+        self.key.expose_secret()
+    }
+
+    /// Decrypts every entry of the vault, setting aside lines that cannot be parsed.
+    pub fn load(&mut self, store: &dyn VaultStore) -> Result<Entries, VaultError> {
+        let contents = store.load(&self.login)?;
+        let (entries, damaged) = RawVault::parse(&contents)?.entries(self.key())?;
+        self.damaged = damaged;
+
+        Ok(entries)
+    }
+
+    /// Lines of the vault that could not be read back as entries during the last load.
+    pub fn damaged(&self) -> &[DamagedLine] {
+        &self.damaged
+    }
+
+    /// Replaces the contents of the vault with `entries`, keeping its KDF parameters.
+    pub fn save(
+        &mut self,
+        store: &mut dyn VaultStore,
+        entries: &Entries,
+    ) -> Result<(), VaultError> {
+        let contents = serialize(
+            &mut self.header,
+            self.key.expose_secret(),
+            entries,
+            &self.damaged,
+        )?;
+
+        store.save(&self.login, &contents)
+    }
+}
+
+/// A vault file split into its header and the lines following it.
+struct RawVault<'a> {
+    header: VaultHeader,
+    /// The header exactly as stored, authenticated as associated data of the sealed blob.
+    header_line: &'a str,
+    body: Vec<&'a str>,
+}
+
+impl<'a> RawVault<'a> {
+    fn parse(contents: &'a str) -> Result<Self, VaultError> {
+        let mut lines = contents.lines().peekable();
+
+        match lines
+            .peek()
+            .map(|line| VaultHeader::parse(line))
+            .transpose()?
+        {
+            Some(Some(header)) => {
+                migration::check_supported(&header)?;
+
+                Ok(RawVault {
+                    header,
+                    header_line: lines.next().unwrap_or_default(),
+                    body: lines.collect(),
+                })
+            }
+            _ => Ok(RawVault {
+                header: VaultHeader::legacy(),
+                header_line: "",
+                body: lines.collect(),
+            }),
+        }
+    }
+
+    fn entries(&self, key: &[u8]) -> Result<(Entries, Vec<DamagedLine>), VaultError> {
+        let decode = match self.header.format_version {
+            0..=2 => record::decode_unescaped,
+            3..=4 => record::decode_escaped_pair,
+            _ => record::decode,
+        };
+
+        if self.header.format_version < 2 {
+            return Ok(record::parse_lines(self.body.iter().copied(), decode));
+        }
+
+        let Some(blob) = self.body.first() else {
+            return Ok((Entries::default(), vec![]));
+        };
+
+        let sealed = BASE64_STANDARD
+            .decode(blob)
+            .map_err(|_| CryptoError::Encoding)?;
+        let plaintext = Zeroizing::new(crypto_utils::open(
+            &sealed,
+            self.header_line.as_bytes(),
+            key,
+        )?);
+        let plaintext = str::from_utf8(&plaintext).map_err(|_| CryptoError::Encoding)?;
+
+        Ok(record::parse_lines(plaintext.lines(), decode))
+    }
+}
+
+/// Seals `entries` under `header`, returning the complete file contents.
+fn serialize(
+    header: &mut VaultHeader,
+    key: &[u8],
+    entries: &Entries,
+    damaged: &[DamagedLine],
+) -> Result<String, VaultError> {
+    header.format_version = FORMAT_VERSION;
+    if header.key_check.is_none() {
+        header.key_check = Some(crypto_utils::encrypt(KEY_CHECK_PLAINTEXT, key)?);
+    }
+
+    let header_line = header.to_string();
+    let plaintext = Zeroizing::new(record::write_lines(entries, damaged));
+    let sealed = crypto_utils::seal(plaintext.as_bytes(), header_line.as_bytes(), key)?;
+
+    Ok(format!(
+        "{header_line}\n{}\n",
+        BASE64_STANDARD.encode(sealed)
+    ))
+}
+
+/// Derives the master key for `login` and verifies it against the vault, upgrading vaults
+/// written in an older format.
+pub fn unlock(
+    store: &mut dyn VaultStore,
+    login: &str,
+    password: SecretString,
+) -> Result<Vault, VaultError> {
+    validate_login(login)?;
+
+    let contents = store.load(login)?;
+    let (header, key) = unlock_contents(&contents, &password)?;
+
+    let mut vault = Vault {
+        login: login.to_string(),
+        header,
+        key,
+        damaged: vec![],
+    };
+    migration::upgrade(store, &mut vault, &contents, &password)?;
+
+    Ok(vault)
+}
+
+/// Finds the newest backup of the vault of `login` that opens with `password` and puts it
+/// back in place of the damaged vault.
+pub fn restore_backup(
+    store: &mut dyn VaultStore,
+    login: &str,
+    password: SecretString,
+) -> Result<Vault, VaultError> {
+    validate_login(login)?;
+
+    for index in 1..=store.backup_count() {
+        let Ok(contents) = store.load_backup(login, index) else {
+            continue;
+        };
+
+        if let Ok((header, key)) = unlock_contents(&contents, &password) {
+            store.restore_backup(login, index)?;
+
+            let mut vault = Vault {
+                login: login.to_string(),
+                header,
+                key,
+                damaged: vec![],
+            };
+            migration::upgrade(store, &mut vault, &contents, &password)?;
+
+            return Ok(vault);
+        }
+    }
+
+    Err(VaultError::NoUsableBackup)
+}
+
+fn unlock_contents(
+    contents: &str,
+    password: &SecretString,
+) -> Result<(VaultHeader, SecretBox<Vec<u8>>), VaultError> {
+    let raw = RawVault::parse(contents)?;
+
+    let key = crypto_utils::hash_password(password, &raw.header.kdf)?;
+
+    let verified = match &raw.header.key_check {
+        Some(key_check) => crypto_utils::decrypt(key_check, key.as_slice()).is_ok(),
+        None if raw.header.format_version >= 2 => raw.entries(key.as_slice()).is_ok(),
+        // Plaintext vaults without a key check are verified against their first entry. Without
+        // one nothing proves the password right, and migrating would seal the vault under it.
+        None => {
+            let first_sealed =
+                record::parse_lines(raw.body.iter().copied(), record::decode_unescaped)
+                    .0
+                    .active
+                    .iter()
+                    .find_map(|entry| BASE64_STANDARD.decode(&entry.password).ok())
+                    .ok_or(VaultError::Corrupted)?;
+
+            crypto_utils::decrypt(&first_sealed, key.as_slice()).is_ok()
+        }
+    };
+
+    if !verified {
+        return Err(VaultError::WrongPassword);
+    }
+
+    // The right key that still cannot open the entries means the file itself is damaged.
+    if raw.entries(key.as_slice()).is_err() {
+        return Err(VaultError::Corrupted);
+    }
+
+    Ok((raw.header, SecretBox::new(Box::new(key.to_vec()))))
+}
+
+/// Creates an empty vault for `login`, refusing to overwrite an existing one.
+pub fn create(
+    store: &mut dyn VaultStore,
+    login: &str,
+    password: SecretString,
+) -> Result<Vault, VaultError> {
+    validate_login(login)?;
+
+    let mut header = VaultHeader::generate();
+    let key = crypto_utils::hash_password(&password, &header.kdf)?;
+    let contents = serialize(&mut header, key.as_slice(), &Entries::default(), &[])?;
+
+    store.create(login, &contents)?;
+
+    Ok(Vault {
+        login: login.to_string(),
+        header,
+        key: SecretBox::new(Box::new(key.to_vec())),
+        damaged: vec![],
+    })
+}
+
+/// Logins double as file names, so anything that could escape the vault directory is rejected.
+fn validate_login(login: &str) -> Result<(), VaultError> {
+    let is_valid = !login.is_empty()
+        && login
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+
+    if is_valid {
+        Ok(())
+    } else {
+        Err(VaultError::InvalidLogin)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto_utils::KdfParams;
+    use memory_store::MemoryStore;
+    use std::fs;
+
+    fn unlocked_vault(store: &mut MemoryStore) -> Vault {
+        create(store, "alice", SecretString::from("correct horse")).unwrap()
+    }
+
+    #[test]
+    fn labels_with_separators_and_line_breaks_survive_saving() {
+        let mut store = MemoryStore::default();
+        let mut vault = unlocked_vault(&mut store);
+        let mut notes = Entry::new(
+            String::from("multi\nline\r\nlabel"),
+            String::from("cGFzcw=="),
+        );
+        notes.notes = String::from("first line\nsecond; line");
+        notes.urls = vec![String::from("https://example.com/?a=b;c=d")];
+        let active = vec![
+            Entry::new(String::from("a=b"), String::from("c2VjcmV0==")),
+            notes,
+            Entry::new(String::from("back\\slash\\"), String::new()),
+        ];
+        let entries = Entries {
+            active,
+            trash: vec![Entry::new(String::from("[trash]"), String::from("=\n"))],
+        };
+
+        vault.save(&mut store, &entries).unwrap();
+
+        assert_eq!(vault.load(&store).unwrap(), entries);
+        assert!(vault.damaged().is_empty());
+    }
+
+    #[test]
+    fn damaged_lines_are_flagged_and_kept_on_save() {
+        let mut store = MemoryStore::default();
+        let mut vault = unlocked_vault(&mut store);
+        let damaged = record::parse_lines(["not a record"].into_iter(), record::decode).1;
+        let contents = serialize(
+            &mut vault.header,
+            vault.key.expose_secret(),
+            &Entries {
+                active: vec![Entry::new(String::from("mail"), String::from("cGFzcw=="))],
+                trash: vec![],
+            },
+            &damaged,
+        )
+        .unwrap();
+        store.save("alice", &contents).unwrap();
+
+        let entries = vault.load(&store).unwrap();
+        assert_eq!(entries.active.len(), 1);
+        assert_eq!(vault.damaged().len(), 1);
+        assert_eq!(vault.damaged()[0].number, 2);
+
+        vault.save(&mut store, &entries).unwrap();
+        vault.load(&store).unwrap();
+        assert_eq!(vault.damaged().len(), 1);
+    }
+
+    #[test]
+    fn legacy_vaults_are_upgraded_and_re_keyed() {
+        let key =
+            crypto_utils::hash_password(&SecretString::from("hunter22"), &KdfParams::legacy())
+                .unwrap();
+        let encrypted =
+            BASE64_STANDARD.encode(crypto_utils::encrypt("s3cret", key.as_slice()).unwrap());
+        let legacy = format!("mail={encrypted}\nbank={encrypted}\n");
+        let mut store = MemoryStore::default();
+        store.create("bob", &legacy).unwrap();
+
+        let mut vault = unlock(&mut store, "bob", SecretString::from("hunter22")).unwrap();
+
+        let upgraded = store.load("bob").unwrap();
+        let header = VaultHeader::parse(upgraded.lines().next().unwrap())
+            .unwrap()
+            .unwrap();
+        assert_eq!(header.format_version, FORMAT_VERSION);
+        assert_ne!(header.kdf.salt, KdfParams::legacy().salt);
+        assert!(header.key_check.is_some());
+
+        // Re-keyed, so the entries are sealed anew and only open with the new key.
+        let active = vault.load(&store).unwrap().active;
+        let labels: Vec<_> = active.iter().map(|entry| entry.label.as_str()).collect();
+        assert_eq!(labels, ["mail", "bank"]);
+        for entry in &active {
+            let sealed = BASE64_STANDARD.decode(&entry.password).unwrap();
+            assert!(crypto_utils::decrypt(&sealed, key.as_slice()).is_err());
+            assert_eq!(
+                crypto_utils::decrypt(&sealed, vault.key()).unwrap(),
+                b"s3cret"
+            );
+        }
+
+        assert!(matches!(
+            unlock(&mut store, "bob", SecretString::from("wrong")),
+            Err(VaultError::WrongPassword)
+        ));
+        unlock(&mut store, "bob", SecretString::from("hunter22")).unwrap();
+    }
+
+    #[test]
+    fn files_under_the_legacy_key_age_out_of_the_backups() {
+        let directory = persistence::TestDirectory::new("upgrade");
+        let mut store = file_store::FileStore::new(directory.0.clone(), 2);
+        let key =
+            crypto_utils::hash_password(&SecretString::from("hunter22"), &KdfParams::legacy())
+                .unwrap();
+        let encrypted =
+            BASE64_STANDARD.encode(crypto_utils::encrypt("s3cret", key.as_slice()).unwrap());
+        store.create("bob", &format!("mail={encrypted}\n")).unwrap();
+
+        let mut vault = unlock(&mut store, "bob", SecretString::from("hunter22")).unwrap();
+        let entries = vault.load(&store).unwrap();
+        for _ in 0..2 {
+            vault.save(&mut store, &entries).unwrap();
+        }
+
+        let mut files = 0;
+        for file in fs::read_dir(&directory.0).unwrap() {
+            let contents = fs::read_to_string(file.unwrap().path()).unwrap();
+            let header = VaultHeader::parse(contents.lines().next().unwrap()).unwrap();
+            assert!(header.is_some_and(|header| header.kdf != KdfParams::legacy()));
+            files += 1;
+        }
+        // The vault and its two backups.
+        assert_eq!(files, 3);
+    }
+
+    #[test]
+    fn legacy_vaults_without_a_readable_entry_accept_no_password() {
+        let mut store = MemoryStore::default();
+        store.create("erin", "").unwrap();
+        store.create("frank", "mail=not base64\n").unwrap();
+
+        for login in ["erin", "frank"] {
+            assert!(matches!(
+                unlock(&mut store, login, SecretString::from("anything")),
+                Err(VaultError::Corrupted)
+            ));
+            assert!(
+                VaultHeader::parse(store.load(login).unwrap().lines().next().unwrap_or(""))
+                    .unwrap()
+                    .is_none()
+            );
+        }
+    }
+
+    #[test]
+    fn headers_asking_for_unbounded_kdf_costs_are_refused() {
+        let mut store = MemoryStore::default();
+        let header = VaultHeader::generate().to_string();
+        let m_cost = format!(";m={};", KdfParams::generate().m_cost);
+        store
+            .create("dave", &header.replace(&m_cost, ";m=4294967295;"))
+            .unwrap();
+
+        assert!(matches!(
+            unlock(&mut store, "dave", SecretString::from("anything")),
+            Err(VaultError::Header(HeaderError::InvalidField("m")))
+        ));
+    }
+
+    #[test]
+    fn vaults_from_newer_versions_are_refused() {
+        let mut store = MemoryStore::default();
+        let mut header = VaultHeader::generate();
+        header.format_version = FORMAT_VERSION + 1;
+        store.create("carol", &format!("{header}\n")).unwrap();
+
+        assert!(matches!(
+            unlock(&mut store, "carol", SecretString::from("anything")),
+            Err(VaultError::UnsupportedFormat(version)) if version == FORMAT_VERSION + 1
+        ));
+    }
+}
@@ -0,0 +1,270 @@
+use std::{
+    ffi::OsString,
+    fs::{self, DirBuilder, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+#[cfg(unix)]
+use std::os::unix::fs::{DirBuilderExt, OpenOptionsExt, PermissionsExt};
+
+/// Number of previous versions kept next to every vault unless configured otherwise,
+/// `.bak.1` being the newest.
+pub const DEFAULT_BACKUP_COUNT: usize = 3;
+
+pub fn backup_path(path: &Path, index: usize) -> PathBuf {
+    with_suffix(path, &format!(".bak.{index}"))
+}
+
+/// Where the `index`-th vault replaced by a restored backup is kept, `.damaged.1` being the
+/// oldest.
+pub fn damaged_path(path: &Path, index: usize) -> PathBuf {
+    with_suffix(path, &format!(".damaged.{index}"))
+}
+
+/// Where the preferences of the account whose vault is at `path` are kept.
+pub fn preferences_path(path: &Path) -> PathBuf {
+    with_suffix(path, ".preferences")
+}
+
+/// Replaces the file at `path` with `contents` so that a crash leaves either the old or
+/// the new version in place, rotating the old version into `backup_count` backups first.
+pub fn write(path: &Path, contents: &[u8], backup_count: usize) -> io::Result<()> {
+    if path.exists() && backup_count > 0 {
+        rotate_backups(path, backup_count)?;
+    }
+
+    replace_atomically(path, contents)
+}
+
+/// Like [`write`], for files that keep no backups.
+pub fn write_without_backup(path: &Path, contents: &[u8]) -> io::Result<()> {
+    replace_atomically(path, contents)
+}
+
+/// Puts backup number `index` back in place of the file at `path`, keeping the
+/// replaced file aside for inspection next to the ones replaced by earlier restores.
+pub fn restore(path: &Path, index: usize) -> io::Result<()> {
+    let contents = fs::read(backup_path(path, index))?;
+
+    if path.exists() {
+        let damaged_path = (1..)
+            .map(|index| damaged_path(path, index))
+            .find(|damaged_path| !damaged_path.exists())
+            .expect("there is always a free index");
+        fs::copy(path, damaged_path)?;
+    }
+
+    replace_atomically(path, &contents)
+}
+
+/// Options for files only the current user may read or write (`0600` on Unix).
+pub fn private_open_options() -> OpenOptions {
+    let mut options = OpenOptions::new();
+
+    #[cfg(unix)]
+    options.mode(0o600);
+
+    options
+}
+
+/// Creates `path` and its missing parents, accessible only to the current user (`0700` on Unix).
+pub fn create_private_directory(path: &Path) -> io::Result<()> {
+    let mut builder = DirBuilder::new();
+    builder.recursive(true);
+
+    #[cfg(unix)]
+    builder.mode(0o700);
+
+    builder.create(path)
+}
+
+/// Makes an existing directory private, as [`create_private_directory`] makes new ones, e.g.
+/// when an earlier version created it readable by everyone.
+#[cfg(unix)]
+pub fn tighten_directory(path: &Path) -> io::Result<()> {
+    if path.is_dir() {
+        fs::set_permissions(path, fs::Permissions::from_mode(0o700))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub fn tighten_directory(_path: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+/// Makes the vault at `path` and the files kept next to it private, as
+/// [`private_open_options`] makes new ones. Other files in the directory are left alone.
+#[cfg(unix)]
+pub fn tighten_vault_files(path: &Path) -> io::Result<()> {
+    let numbered = |numbered_path: fn(&Path, usize) -> PathBuf| {
+        (1..)
+            .map(move |index| numbered_path(path, index))
+            .take_while(|numbered_path| numbered_path.exists())
+    };
+    let preferences_path = preferences_path(path);
+
+    let candidates = [
+        path.to_path_buf(),
+        with_suffix(path, ".tmp"),
+        with_suffix(&preferences_path, ".tmp"),
+        preferences_path,
+    ]
+    .into_iter()
+    .chain(numbered(backup_path))
+    .chain(numbered(damaged_path));
+
+    for candidate in candidates {
+        if fs::symlink_metadata(&candidate).is_ok_and(|metadata| metadata.is_file()) {
+            fs::set_permissions(&candidate, fs::Permissions::from_mode(0o600))?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub fn tighten_vault_files(_path: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+fn rotate_backups(path: &Path, backup_count: usize) -> io::Result<()> {
+    for index in (1..backup_count).rev() {
+        let older = backup_path(path, index);
+        if older.exists() {
+            fs::rename(older, backup_path(path, index + 1))?;
+        }
+    }
+
+    // Copy rather than rename so the live vault never disappears, even briefly.
+    fs::copy(path, backup_path(path, 1))?;
+
+    Ok(())
+}
+
+fn replace_atomically(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let temporary_path = with_suffix(path, ".tmp");
+
+    let mut file = private_open_options()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(&temporary_path)?;
+    file.write_all(contents)?;
+    file.sync_all()?;
+    drop(file);
+
+    fs::rename(&temporary_path, path)?;
+    sync_parent_directory(path)
+}
+
+/// Makes the rename itself durable, which on Unix requires syncing the directory entry.
+#[cfg(unix)]
+fn sync_parent_directory(path: &Path) -> io::Result<()> {
+    match path.parent() {
+        Some(directory_path) => File::open(directory_path)?.sync_all(),
+        None => Ok(()),
+    }
+}
+
+#[cfg(not(unix))]
+fn sync_parent_directory(_path: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut file_name = path.file_name().map(OsString::from).unwrap_or_default();
+    file_name.push(suffix);
+
+    path.with_file_name(file_name)
+}
+
+/// A fresh directory for `name`, removed when the test is done with it.
+#[cfg(test)]
+pub(super) struct TestDirectory(pub PathBuf);
+
+#[cfg(test)]
+impl TestDirectory {
+    pub fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("rusty-lock-{}-{name}", std::process::id()));
+        let _ = fs::remove_dir_all(&path);
+        create_private_directory(&path).unwrap();
+
+        TestDirectory(path)
+    }
+}
+
+#[cfg(test)]
+impl Drop for TestDirectory {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn only_the_files_of_a_vault_are_made_private() {
+        let directory = TestDirectory::new("permissions");
+        let path = directory.0.join("alice");
+        let vault_files = [
+            path.clone(),
+            backup_path(&path, 1),
+            backup_path(&path, 2),
+            preferences_path(&path),
+        ];
+        let other_files = [directory.0.join("notes"), directory.0.join("build.sh")];
+        for file in vault_files.iter().chain(&other_files) {
+            fs::write(file, "contents").unwrap();
+            fs::set_permissions(file, fs::Permissions::from_mode(0o755)).unwrap();
+        }
+        fs::set_permissions(&directory.0, fs::Permissions::from_mode(0o755)).unwrap();
+
+        tighten_vault_files(&path).unwrap();
+
+        let mode = |path: &Path| fs::metadata(path).unwrap().permissions().mode() & 0o777;
+        for file in &vault_files {
+            assert_eq!(mode(file), 0o600, "{}", file.display());
+        }
+        for file in &other_files {
+            assert_eq!(mode(file), 0o755, "{}", file.display());
+        }
+        assert_eq!(mode(&directory.0), 0o755);
+
+        tighten_directory(&directory.0).unwrap();
+        assert_eq!(mode(&directory.0), 0o700);
+    }
+
+    #[test]
+    fn backups_rotate_and_restores_keep_every_damaged_vault() {
+        let directory = TestDirectory::new("backups");
+        let path = directory.0.join("alice");
+
+        for version in ["one", "two", "three", "four"] {
+            write(&path, version.as_bytes(), 2).unwrap();
+        }
+        assert_eq!(fs::read_to_string(backup_path(&path, 1)).unwrap(), "three");
+        assert_eq!(fs::read_to_string(backup_path(&path, 2)).unwrap(), "two");
+        assert!(!backup_path(&path, 3).exists());
+
+        fs::write(&path, "damaged once").unwrap();
+        restore(&path, 1).unwrap();
+        fs::write(&path, "damaged twice").unwrap();
+        restore(&path, 2).unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "two");
+        assert_eq!(
+            fs::read_to_string(damaged_path(&path, 1)).unwrap(),
+            "damaged once"
+        );
+        assert_eq!(
+            fs::read_to_string(damaged_path(&path, 2)).unwrap(),
+            "damaged twice"
+        );
+    }
+}
@@ -0,0 +1,597 @@
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+
+use crate::{
+    strength::MAX_SCORE,
+    totp::{Algorithm, TotpParameters},
+};
+
+use super::{
+    entry::{CustomField, Entry, OneTimePassword, PreviousPassword},
+    Entries,
+};
+
+/// Line after which every entry is in the trash. It has no `=`, so no entry can look like it.
+pub const TRASH_SECTION: &str = "[trash]";
+
+/// Why a single line of a vault could not be read back as an [`Entry`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RecordError {
+    MissingSeparator,
+    ExtraSeparator,
+    InvalidEscape(char),
+    TrailingEscape,
+    MissingField(&'static str),
+    UnknownField(String),
+    InvalidTimestamp(String),
+    InvalidHistory,
+    InvalidStrength,
+    InvalidTotp,
+}
+
+impl fmt::Display for RecordError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecordError::MissingSeparator => write!(f, "no `=` between a field name and value"),
+            RecordError::ExtraSeparator => write!(f, "more than one unescaped `=` in a field"),
+            RecordError::InvalidEscape(c) => write!(f, "unknown escape sequence `\\{c}`"),
+            RecordError::TrailingEscape => write!(f, "the line ends in the middle of an escape"),
+            RecordError::MissingField(name) => write!(f, "the `{name}` field is missing"),
+            RecordError::UnknownField(name) => write!(f, "unknown field `{name}`"),
+            RecordError::InvalidTimestamp(name) => write!(f, "the `{name}` field is not a time"),
+            RecordError::InvalidStrength => write!(f, "the `strength` field is not a score"),
+            RecordError::InvalidTotp => write!(
+                f,
+                "the `totp` field is not an `<algorithm>,<digits>,<period>,<secret>` list"
+            ),
+            RecordError::InvalidHistory => {
+                write!(f, "a `history` field is not a `<time>,<password>` pair")
+            }
+        }
+    }
+}
+
+/// A line that was skipped while reading a vault, kept verbatim so saving does not lose it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DamagedLine {
+    /// 1-based position among the entry lines of the vault.
+    pub number: usize,
+    pub error: RecordError,
+    pub(super) text: String,
+    /// Whether it was read from the trash, where it is written back.
+    pub(super) in_trash: bool,
+}
+
+impl fmt::Display for DamagedLine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.number, self.error)
+    }
+}
+
+/// Writes `entry` as a single line of `name=value` fields separated by `;`, escaping `\`,
+/// `=`, `;` and line breaks so that any value survives [`decode`]. Empty optional fields
+/// are left out, fields holding a list are repeated.
+pub fn encode(entry: &Entry) -> String {
+    let mut fields = vec![
+        (String::from("label"), entry.label.clone()),
+        (String::from("password"), entry.password.clone()),
+    ];
+
+    if !entry.username.is_empty() {
+        fields.push((String::from("username"), entry.username.clone()));
+    }
+    for url in &entry.urls {
+        fields.push((String::from("url"), url.clone()));
+    }
+    for tag in &entry.tags {
+        fields.push((String::from("tag"), tag.clone()));
+    }
+    if !entry.notes.is_empty() {
+        fields.push((String::from("notes"), entry.notes.clone()));
+    }
+    if let Some(strength) = entry.strength {
+        fields.push((String::from("strength"), strength.to_string()));
+    }
+    for (name, time) in [
+        ("created", entry.created),
+        ("modified", entry.modified),
+        ("used", entry.last_used),
+    ] {
+        if let Some(time) = time {
+            fields.push((name.to_string(), time.timestamp().to_string()));
+        }
+    }
+    for previous in &entry.history {
+        fields.push((
+            String::from("history"),
+            format!("{},{}", previous.replaced.timestamp(), previous.password),
+        ));
+    }
+    if let Some(totp) = &entry.totp {
+        let parameters = &totp.parameters;
+        let mut value = format!(
+            "{},{},{},{}",
+            parameters.algorithm.name(),
+            parameters.digits,
+            parameters.period,
+            totp.secret
+        );
+        if let Some(counter) = parameters.counter {
+            value.push_str(&format!(",{counter}"));
+        }
+        fields.push((String::from("totp"), value));
+    }
+    for custom_field in &entry.custom_fields {
+        let prefix = if custom_field.secret {
+            "secret"
+        } else {
+            "field"
+        };
+        fields.push((
+            format!("{prefix}.{}", custom_field.name),
+            custom_field.value.clone(),
+        ));
+    }
+
+    fields
+        .iter()
+        .map(|(name, value)| format!("{}={}", escape(name), escape(value)))
+        .collect::<Vec<_>>()
+        .join(";")
+}
+
+/// Reads back a line written by [`encode`].
+pub fn decode(line: &str) -> Result<Entry, RecordError> {
+    let mut entry = Entry::default();
+    let mut label = None;
+    let mut password = None;
+
+    for (name, value) in split_fields(line, true)? {
+        match name.as_str() {
+            "label" => label = Some(value),
+            "password" => password = Some(value),
+            "username" => entry.username = value,
+            "url" => entry.urls.push(value),
+            "tag" => entry.tags.push(value),
+            "notes" => entry.notes = value,
+            "created" => entry.created = Some(parse_time(&name, &value)?),
+            "modified" => entry.modified = Some(parse_time(&name, &value)?),
+            "used" => entry.last_used = Some(parse_time(&name, &value)?),
+            "history" => entry.history.push(parse_history(&value)?),
+            "totp" => entry.totp = Some(parse_totp(&value).ok_or(RecordError::InvalidTotp)?),
+            "strength" => {
+                entry.strength = Some(
+                    value
+                        .parse()
+                        .ok()
+                        .filter(|score| *score <= MAX_SCORE)
+                        .ok_or(RecordError::InvalidStrength)?,
+                )
+            }
+            _ => {
+                let (secret, custom_name) = match name.split_once('.') {
+                    Some(("field", custom_name)) => (false, custom_name),
+                    Some(("secret", custom_name)) => (true, custom_name),
+                    _ => return Err(RecordError::UnknownField(name)),
+                };
+
+                entry.custom_fields.push(CustomField {
+                    name: custom_name.to_string(),
+                    value,
+                    secret,
+                });
+            }
+        }
+    }
+
+    entry.label = label.ok_or(RecordError::MissingField("label"))?;
+    entry.password = password.ok_or(RecordError::MissingField("password"))?;
+
+    Ok(entry)
+}
+
+/// Reads a `label=password` line of formats 3 and 4, escaped like [`encode`] but without
+/// any other field.
+pub fn decode_escaped_pair(line: &str) -> Result<Entry, RecordError> {
+    let (label, password) = split_fields(line, false)?
+        .pop()
+        .ok_or(RecordError::MissingSeparator)?;
+
+    Ok(Entry {
+        label,
+        password,
+        ..Default::default()
+    })
+}
+
+/// Reads a line of the unescaped format used before format 3, where the label ends at the
+/// first `=` and everything after it, base64 padding included, is the value.
+pub fn decode_unescaped(line: &str) -> Result<Entry, RecordError> {
+    let (label, password) = line.split_once('=').ok_or(RecordError::MissingSeparator)?;
+
+    Ok(Entry {
+        label: label.to_string(),
+        password: password.to_string(),
+        ..Default::default()
+    })
+}
+
+/// Decodes every non-blank line, setting aside the ones that fail instead of giving up.
+pub fn parse_lines<'a>(
+    lines: impl Iterator<Item = &'a str>,
+    decode: fn(&str) -> Result<Entry, RecordError>,
+) -> (Entries, Vec<DamagedLine>) {
+    let mut entries = Entries::default();
+    let mut damaged = vec![];
+    let mut in_trash = false;
+
+    for (index, line) in lines.enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        if line == TRASH_SECTION {
+            in_trash = true;
+            continue;
+        }
+
+        match decode(line) {
+            Ok(entry) if in_trash => entries.trash.push(entry),
+            Ok(entry) => entries.active.push(entry),
+            Err(error) => damaged.push(DamagedLine {
+                number: index + 1,
+                error,
+                text: line.to_string(),
+                in_trash,
+            }),
+        }
+    }
+
+    (entries, damaged)
+}
+
+/// Writes one line per entry, followed by the trash if it isn't empty. Damaged lines are
+/// written back at the end of the section they were read from.
+pub fn write_lines(entries: &Entries, damaged: &[DamagedLine]) -> String {
+    let (damaged_trash, damaged_active): (Vec<&DamagedLine>, Vec<&DamagedLine>) =
+        damaged.iter().partition(|line| line.in_trash);
+
+    let mut lines: Vec<String> = entries.active.iter().map(encode).collect();
+    lines.extend(damaged_active.iter().map(|line| line.text.clone()));
+
+    if !entries.trash.is_empty() || !damaged_trash.is_empty() {
+        lines.push(TRASH_SECTION.to_string());
+        lines.extend(entries.trash.iter().map(encode));
+        lines.extend(damaged_trash.iter().map(|line| line.text.clone()));
+    }
+
+    lines.into_iter().map(|line| line + "\n").collect()
+}
+
+/// Unescapes the `name=value` pairs of `line`, which are separated by unescaped `;` when
+/// `separated`, and otherwise make up a single pair.
+fn split_fields(line: &str, separated: bool) -> Result<Vec<(String, String)>, RecordError> {
+    let mut fields = vec![];
+    let mut name = None;
+    let mut current = String::new();
+    let mut chars = line.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('\\') => current.push('\\'),
+                Some('=') => current.push('='),
+                Some(';') => current.push(';'),
+                Some('n') => current.push('\n'),
+                Some('r') => current.push('\r'),
+                Some(other) => return Err(RecordError::InvalidEscape(other)),
+                None => return Err(RecordError::TrailingEscape),
+            },
+            '=' if name.is_some() => return Err(RecordError::ExtraSeparator),
+            '=' => name = Some(std::mem::take(&mut current)),
+            ';' if separated => {
+                let name = name.take().ok_or(RecordError::MissingSeparator)?;
+                fields.push((name, std::mem::take(&mut current)));
+            }
+            c => current.push(c),
+        }
+    }
+
+    let name = name.ok_or(RecordError::MissingSeparator)?;
+    fields.push((name, current));
+
+    Ok(fields)
+}
+
+fn parse_time(name: &str, value: &str) -> Result<DateTime<Utc>, RecordError> {
+    value
+        .parse()
+        .ok()
+        .and_then(|seconds| DateTime::from_timestamp(seconds, 0))
+        .ok_or_else(|| RecordError::InvalidTimestamp(name.to_string()))
+}
+
+/// Reads a `<unix seconds>,<base64>` history field. Base64 never contains a `,`.
+fn parse_history(value: &str) -> Result<PreviousPassword, RecordError> {
+    let (replaced, password) = value.split_once(',').ok_or(RecordError::InvalidHistory)?;
+    let replaced = parse_time("history", replaced).map_err(|_| RecordError::InvalidHistory)?;
+
+    Ok(PreviousPassword {
+        password: password.to_string(),
+        replaced,
+    })
+}
+
+/// Reads an `<algorithm>,<digits>,<period>,<base64>[,<counter>]` TOTP field, the counter
+/// being there for HOTP.
+fn parse_totp(value: &str) -> Option<OneTimePassword> {
+    let mut parts = value.split(',');
+    let mut parameters = TotpParameters {
+        algorithm: Algorithm::from_name(parts.next()?)?,
+        digits: parts.next()?.parse().ok()?,
+        period: parts.next()?.parse().ok()?,
+        counter: None,
+    };
+    let secret = parts.next()?.to_string();
+    if let Some(counter) = parts.next() {
+        parameters.counter = Some(counter.parse().ok()?);
+    }
+
+    if parts.next().is_some() || parameters.validate().is_err() {
+        return None;
+    }
+
+    Some(OneTimePassword { secret, parameters })
+}
+
+fn escape(field: &str) -> String {
+    let mut escaped = String::with_capacity(field.len());
+
+    for c in field.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '=' => escaped.push_str("\\="),
+            ';' => escaped.push_str("\\;"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use base64::{prelude::BASE64_STANDARD, Engine};
+    use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+    const ROUNDS: usize = 2000;
+
+    /// Characters that the format has to treat specially, mixed into otherwise random text.
+    const TRICKY: &[char] = &[
+        '=', ';', '.', '\\', '\n', '\r', 'n', 'r', ' ', '\t', '\0', 'é', '🔒',
+    ];
+
+    fn arbitrary_text(rng: &mut StdRng) -> String {
+        let length = rng.gen_range(0..40);
+
+        (0..length)
+            .map(|_| {
+                if rng.gen_bool(0.5) {
+                    *TRICKY.choose(rng).unwrap()
+                } else {
+                    rng.gen::<char>()
+                }
+            })
+            .collect()
+    }
+
+    fn arbitrary_ciphertext(rng: &mut StdRng) -> String {
+        let length = rng.gen_range(0..64);
+        let bytes: Vec<u8> = (0..length).map(|_| rng.gen()).collect();
+
+        BASE64_STANDARD.encode(bytes)
+    }
+
+    fn arbitrary_texts(rng: &mut StdRng) -> Vec<String> {
+        (0..rng.gen_range(0..4))
+            .map(|_| arbitrary_text(rng))
+            .collect()
+    }
+
+    fn arbitrary_time(rng: &mut StdRng) -> Option<DateTime<Utc>> {
+        rng.gen_bool(0.7)
+            .then(|| DateTime::from_timestamp(rng.gen_range(0..4_000_000_000), 0).unwrap())
+    }
+
+    fn arbitrary_entry(rng: &mut StdRng) -> Entry {
+        Entry {
+            label: arbitrary_text(rng),
+            password: arbitrary_ciphertext(rng),
+            username: arbitrary_text(rng),
+            urls: arbitrary_texts(rng),
+            notes: arbitrary_text(rng),
+            tags: arbitrary_texts(rng),
+            custom_fields: (0..rng.gen_range(0..4))
+                .map(|_| {
+                    let secret = rng.gen_bool(0.5);
+                    CustomField {
+                        name: arbitrary_text(rng),
+                        value: if secret {
+                            arbitrary_ciphertext(rng)
+                        } else {
+                            arbitrary_text(rng)
+                        },
+                        secret,
+                    }
+                })
+                .collect(),
+            history: (0..rng.gen_range(0..4))
+                .map(|_| PreviousPassword {
+                    password: arbitrary_ciphertext(rng),
+                    replaced: arbitrary_time(rng).unwrap_or_default(),
+                })
+                .collect(),
+            strength: rng.gen_bool(0.5).then(|| rng.gen_range(0..=MAX_SCORE)),
+            totp: rng.gen_bool(0.5).then(|| OneTimePassword {
+                secret: arbitrary_ciphertext(rng),
+                parameters: TotpParameters {
+                    digits: rng.gen_range(6..=10),
+                    period: rng.gen_range(1..=120),
+                    algorithm: *Algorithm::ALL.choose(rng).unwrap(),
+                    counter: rng.gen_bool(0.5).then(|| rng.gen()),
+                },
+            }),
+            created: arbitrary_time(rng),
+            modified: arbitrary_time(rng),
+            last_used: arbitrary_time(rng),
+        }
+    }
+
+    fn pair(label: &str, password: &str) -> Entry {
+        Entry {
+            label: label.to_string(),
+            password: password.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn encoded_entries_fit_on_one_line() {
+        let mut rng = StdRng::seed_from_u64(1);
+
+        for _ in 0..ROUNDS {
+            let line = encode(&arbitrary_entry(&mut rng));
+
+            assert!(!line.contains(['\n', '\r']), "{line:?}");
+        }
+    }
+
+    #[test]
+    fn arbitrary_labels_and_ciphertexts_round_trip() {
+        let mut rng = StdRng::seed_from_u64(2);
+
+        for _ in 0..ROUNDS {
+            let entry = pair(&arbitrary_text(&mut rng), &arbitrary_ciphertext(&mut rng));
+
+            assert_eq!(decode(&encode(&entry)), Ok(entry));
+        }
+    }
+
+    #[test]
+    fn arbitrary_entries_round_trip() {
+        let mut rng = StdRng::seed_from_u64(3);
+
+        for _ in 0..ROUNDS {
+            let entry = arbitrary_entry(&mut rng);
+
+            assert_eq!(decode(&encode(&entry)), Ok(entry));
+        }
+    }
+
+    #[test]
+    fn whole_files_round_trip() {
+        let mut rng = StdRng::seed_from_u64(4);
+
+        let arbitrary_entries = |rng: &mut StdRng| -> Vec<Entry> {
+            (0..rng.gen_range(0..20))
+                .map(|_| arbitrary_entry(rng))
+                .collect()
+        };
+
+        for _ in 0..ROUNDS / 10 {
+            let entries = Entries {
+                active: arbitrary_entries(&mut rng),
+                trash: arbitrary_entries(&mut rng),
+            };
+            let text = write_lines(&entries, &[]);
+
+            let (parsed, damaged) = parse_lines(text.lines(), decode);
+
+            assert!(damaged.is_empty(), "{damaged:?}");
+            // Every entry has a label and a password field, so no line is ever blank.
+            assert_eq!(parsed, entries);
+        }
+    }
+
+    #[test]
+    fn damaged_lines_stay_in_their_section() {
+        let text = "label=mail;password=a\nbroken live\n[trash]\nlabel=old;password=b\n\
+                    broken trash\n";
+
+        let (entries, damaged) = parse_lines(text.lines(), decode);
+        let written = write_lines(&entries, &damaged);
+
+        assert_eq!(written, text);
+
+        let (_, damaged) = parse_lines(text.lines(), decode);
+        let only_damage = write_lines(&Entries::default(), &damaged);
+        assert_eq!(only_damage, "broken live\n[trash]\nbroken trash\n");
+    }
+
+    #[test]
+    fn garbage_never_panics() {
+        let mut rng = StdRng::seed_from_u64(5);
+
+        for _ in 0..ROUNDS * 5 {
+            let line = arbitrary_text(&mut rng);
+
+            let _ = decode(&line);
+            let _ = decode_escaped_pair(&line);
+            let _ = decode_unescaped(&line);
+            let _ = parse_lines(line.lines(), decode);
+        }
+    }
+
+    #[test]
+    fn damaged_lines_are_reported_and_skipped() {
+        let text = "label=good;password=value\nno separator\n\nlabel=bad\\q;password=x\n\
+                    label=a=b;password=c\nlabel=trailing\\\nlabel=no password\n\
+                    label=x;password=y;color=red\nlabel=x;password=y;used=soon\n\
+                    label=x;password=y;history=c2VjcmV0\n\
+                    label=x;password=y;strength=5\n\
+                    label=x;password=y;totp=MD5,6,30,c2VjcmV0\n\
+                    label=last;password=one";
+
+        let (entries, damaged) = parse_lines(text.lines(), decode);
+
+        assert!(entries.trash.is_empty());
+        assert_eq!(
+            entries.active,
+            vec![pair("good", "value"), pair("last", "one")]
+        );
+        assert_eq!(
+            damaged
+                .iter()
+                .map(|line| (line.number, line.error.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                (2, RecordError::MissingSeparator),
+                (4, RecordError::InvalidEscape('q')),
+                (5, RecordError::ExtraSeparator),
+                (6, RecordError::TrailingEscape),
+                (7, RecordError::MissingField("password")),
+                (8, RecordError::UnknownField(String::from("color"))),
+                (9, RecordError::InvalidTimestamp(String::from("used"))),
+                (10, RecordError::InvalidHistory),
+                (11, RecordError::InvalidStrength),
+                (12, RecordError::InvalidTotp),
+            ]
+        );
+    }
+
+    #[test]
+    fn older_lines_keep_base64_padding() {
+        assert_eq!(
+            decode_unescaped("mail=c2VjcmV0=="),
+            Ok(pair("mail", "c2VjcmV0=="))
+        );
+        assert_eq!(
+            decode_escaped_pair("a\\=b;c=c2VjcmV0\\=\\="),
+            Ok(pair("a=b;c", "c2VjcmV0=="))
+        );
+    }
+}
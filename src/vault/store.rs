@@ -0,0 +1,42 @@
+use super::VaultError;
+
+/// Where vault files live, keyed by login.
+///
+/// Stores only move opaque vault contents around; sealing and parsing them is done by
+/// [`super::Vault`], so a backend never sees anything but ciphertext.
+pub trait VaultStore {
+    /// Fails with [`VaultError::AccountNotFound`] when `login` has no vault.
+    fn load(&self, login: &str) -> Result<String, VaultError>;
+
+    /// Replaces the vault of `login`, which must already exist.
+    fn save(&mut self, login: &str, contents: &str) -> Result<(), VaultError>;
+
+    /// Stores a new vault, failing with [`VaultError::AccountExists`] instead of overwriting.
+    fn create(&mut self, login: &str, contents: &str) -> Result<(), VaultError>;
+
+    fn list_accounts(&self) -> Result<Vec<String>, VaultError>;
+
+    fn delete(&mut self, login: &str) -> Result<(), VaultError>;
+
+    /// Preferences `login` saved in an earlier session, `None` if there are none yet.
+    ///
+    /// Unlike vaults, preferences are stored in the clear and must not hold secrets.
+    fn load_preferences(&self, login: &str) -> Result<Option<String>, VaultError>;
+
+    fn save_preferences(&mut self, login: &str, contents: &str) -> Result<(), VaultError>;
+
+    /// How many previous versions of each vault are kept, see [`VaultStore::load_backup`].
+    fn backup_count(&self) -> usize {
+        0
+    }
+
+    /// Contents of the `index`-th previous version of the vault, `1` being the newest.
+    fn load_backup(&self, _login: &str, _index: usize) -> Result<String, VaultError> {
+        Err(VaultError::NoUsableBackup)
+    }
+
+    /// Puts the `index`-th previous version back in place of the current vault.
+    fn restore_backup(&mut self, _login: &str, _index: usize) -> Result<(), VaultError> {
+        Err(VaultError::NoUsableBackup)
+    }
+}